@@ -0,0 +1,511 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Rendering to Surfaces with EGL.
+//!
+//! Mesa's EGL can use a Device as its native display and a Surface as a
+//! native window (```EGL_KHR_platform_gbm```). A ```Display``` is created for
+//! a Device, a ```Config``` whose native visual is the format of the Surface
+//! is chosen, and a ```WindowSurface``` is created to render to it. After
+//! each frame ```WindowSurface::swap_buffers()``` swaps the buffers and locks
+//! the new front buffer, which can then be scanned out.
+//!
+//! libEGL is linked, even with the ```dlopen``` feature.
+//!
+//! # Example
+//! ```no_run
+//! extern crate gbm_rs as gbm;
+//!
+//! use gbm::egl::{self, Display};
+//! use gbm::{format, BufferUsage, Device, Surface};
+//!
+//! let device = Device::open("/dev/dri/card0").unwrap();
+//! let surface = Surface::new(&device, 1920, 1080, format::XRGB8888,
+//!                            BufferUsage::SCANOUT | BufferUsage::RENDERING).unwrap();
+//!
+//! let display = Display::new(&device).unwrap();
+//! let config = display.choose_config(format::XRGB8888, &[
+//!     egl::EGL_RENDERABLE_TYPE, egl::EGL_OPENGL_ES2_BIT,
+//! ]).unwrap();
+//! let context = display.create_context(config, &[egl::EGL_CONTEXT_CLIENT_VERSION, 2]).unwrap();
+//! let window = display.create_window_surface(config, &surface).unwrap();
+//!
+//! context.make_current(&window).unwrap();
+//!
+//! loop {
+//!     // Render with OpenGL ES
+//!
+//!     let buffer = window.swap_buffers().unwrap();
+//!
+//!     // Scan out the buffer, and keep it locked until the page flip has completed
+//!
+//!     buffer.release();
+//! }
+//! ```
+
+use libc::{c_void, intptr_t};
+use std::ptr;
+
+use error::{Error, Operation, Result};
+use format::Format;
+#[cfg(feature = "software")]
+use software;
+use {Device, LockedBuffer, Surface};
+
+/// A 32 bit EGL integer, used for attribute lists
+pub type EGLint = i32;
+
+type EGLBoolean = u32;
+type EGLenum = u32;
+type EGLAttrib = intptr_t;
+type EGLDisplay = *mut c_void;
+type EGLConfig = *mut c_void;
+type EGLSurface = *mut c_void;
+type EGLContext = *mut c_void;
+
+const EGL_FALSE: EGLBoolean = 0;
+const EGL_NONE: EGLint = 0x3038;
+const EGL_SUCCESS: EGLint = 0x3000;
+const EGL_BAD_PARAMETER: EGLint = 0x300c;
+const EGL_NATIVE_VISUAL_ID: EGLint = 0x302e;
+const EGL_SURFACE_TYPE: EGLint = 0x3033;
+const EGL_WINDOW_BIT: EGLint = 0x0004;
+const EGL_OPENGL_ES_API: EGLenum = 0x30a0;
+const EGL_PLATFORM_GBM_KHR: EGLenum = 0x31d7;
+const EGL_DRAW: EGLint = 0x3059;
+const EGL_READ: EGLint = 0x305a;
+
+/// ```EGL_RED_SIZE```
+pub const EGL_RED_SIZE: EGLint = 0x3024;
+/// ```EGL_GREEN_SIZE```
+pub const EGL_GREEN_SIZE: EGLint = 0x3023;
+/// ```EGL_BLUE_SIZE```
+pub const EGL_BLUE_SIZE: EGLint = 0x3022;
+/// ```EGL_ALPHA_SIZE```
+pub const EGL_ALPHA_SIZE: EGLint = 0x3021;
+/// ```EGL_DEPTH_SIZE```
+pub const EGL_DEPTH_SIZE: EGLint = 0x3025;
+/// ```EGL_STENCIL_SIZE```
+pub const EGL_STENCIL_SIZE: EGLint = 0x3026;
+/// ```EGL_SAMPLES```
+pub const EGL_SAMPLES: EGLint = 0x3031;
+/// ```EGL_RENDERABLE_TYPE```
+pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+/// ```EGL_OPENGL_ES2_BIT```, a value for ```EGL_RENDERABLE_TYPE```
+pub const EGL_OPENGL_ES2_BIT: EGLint = 0x0004;
+/// ```EGL_OPENGL_ES3_BIT```, a value for ```EGL_RENDERABLE_TYPE```
+pub const EGL_OPENGL_ES3_BIT: EGLint = 0x0040;
+/// ```EGL_CONTEXT_CLIENT_VERSION```
+pub const EGL_CONTEXT_CLIENT_VERSION: EGLint = 0x3098;
+
+#[link(name = "EGL")]
+extern "C" {
+    fn eglGetError() -> EGLint;
+    fn eglGetPlatformDisplay(platform: EGLenum, native_display: *mut c_void,
+                             attrib_list: *const EGLAttrib) -> EGLDisplay;
+    fn eglInitialize(dpy: EGLDisplay, major: *mut EGLint, minor: *mut EGLint) -> EGLBoolean;
+    fn eglTerminate(dpy: EGLDisplay) -> EGLBoolean;
+    fn eglChooseConfig(dpy: EGLDisplay, attrib_list: *const EGLint, configs: *mut EGLConfig,
+                       config_size: EGLint, num_config: *mut EGLint) -> EGLBoolean;
+    fn eglGetConfigAttrib(dpy: EGLDisplay, config: EGLConfig,
+                          attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+    fn eglCreatePlatformWindowSurface(dpy: EGLDisplay, config: EGLConfig,
+                                      native_window: *mut c_void,
+                                      attrib_list: *const EGLAttrib) -> EGLSurface;
+    fn eglDestroySurface(dpy: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+    fn eglBindAPI(api: EGLenum) -> EGLBoolean;
+    fn eglCreateContext(dpy: EGLDisplay, config: EGLConfig, share_context: EGLContext,
+                        attrib_list: *const EGLint) -> EGLContext;
+    fn eglDestroyContext(dpy: EGLDisplay, ctx: EGLContext) -> EGLBoolean;
+    fn eglMakeCurrent(dpy: EGLDisplay, draw: EGLSurface, read: EGLSurface,
+                      ctx: EGLContext) -> EGLBoolean;
+    fn eglGetCurrentDisplay() -> EGLDisplay;
+    fn eglGetCurrentSurface(readdraw: EGLint) -> EGLSurface;
+    fn eglSwapBuffers(dpy: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+}
+
+/// Build an error for a failed EGL call from ```eglGetError()```
+unsafe fn last_error(op: Operation) -> Error {
+    match eglGetError() {
+        EGL_SUCCESS => Error::Egl { op, code: EGL_BAD_PARAMETER },
+        code => Error::Egl { op, code },
+    }
+}
+
+/// Append ```EGL_NONE``` to an attribute list
+fn terminate(attribs: &[EGLint]) -> Vec<EGLint> {
+    let mut list = attribs.to_vec();
+    list.push(EGL_NONE);
+    list
+}
+
+/// Analogous to EGLDisplay
+///
+/// An initialized EGL display for a Device, terminated when dropped.
+/// EGL returns the same display for every call with the same Device, so only
+/// one Display can exist for each Device at a time.
+pub struct Display<'dev> {
+    ptr: EGLDisplay,
+    dev: &'dev Device,
+    version: (i32, i32),
+}
+
+impl<'dev> Display<'dev> {
+    /// Get and initialize the EGL display of a Device
+    ///
+    /// # Arguments
+    ///
+    /// dev: The Device to render with
+    ///
+    /// # Returns
+    ///
+    /// The initialized Display.
+    /// If the Device already has a Display ```Error::DisplayInUse``` will be
+    /// returned, and if EGL doesn't support the Device another ```Error```.
+    /// Software Devices are never supported.
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate gbm_rs as gbm;
+    ///
+    /// let device = gbm::Device::open("/dev/dri/card0").unwrap();
+    /// let display = gbm::egl::Display::new(&device).unwrap();
+    ///
+    /// println!("EGL {}.{}", display.version().0, display.version().1);
+    /// ```
+    pub fn new(dev: &'dev Device) -> Result<Display<'dev>> {
+        // EGL would treat the software device as a libgbm one
+        #[cfg(feature = "software")]
+        {
+            if ptr::eq(dev.api, &software::API) {
+                return Err(Error::unsupported(Operation::GetDisplay));
+            }
+        }
+
+        if dev.egl_display.get() {
+            return Err(Error::DisplayInUse);
+        }
+
+        unsafe {
+            let ptr = eglGetPlatformDisplay(EGL_PLATFORM_GBM_KHR, dev.ptr as *mut c_void,
+                                            ptr::null());
+
+            if ptr.is_null() {
+                return Err(last_error(Operation::GetDisplay));
+            }
+
+            let (mut major, mut minor) = (0, 0);
+
+            if eglInitialize(ptr, &mut major, &mut minor) == EGL_FALSE {
+                return Err(last_error(Operation::GetDisplay));
+            }
+
+            dev.egl_display.set(true);
+
+            Ok(Display { ptr, dev, version: (major, minor) })
+        }
+    }
+
+    /// Get the EGL version supported by the Display
+    ///
+    /// # Returns
+    ///
+    /// The major and minor version
+    pub fn version(&self) -> (i32, i32) {
+        self.version
+    }
+
+    /// Choose a window config rendering in a format
+    ///
+    /// Configs are chosen by ```eglChooseConfig()```, then the first whose
+    /// ```EGL_NATIVE_VISUAL_ID``` is the format is used, as Surfaces created
+    /// with a different format can't be rendered to with it.
+    ///
+    /// # Arguments
+    ///
+    /// format: The format of the Surfaces the config will be used with
+    ///
+    /// attribs: Pairs of attributes and values the config must match, without
+    /// the terminating ```EGL_NONE```. ```EGL_SURFACE_TYPE``` is always
+    /// ```EGL_WINDOW_BIT```.
+    ///
+    /// # Returns
+    ///
+    /// The first matching Config.
+    /// If none have the format ```Error::NoMatchingConfig``` will be returned.
+    pub fn choose_config(&self, format: Format, attribs: &[EGLint]) -> Result<Config> {
+        let mut list = vec![EGL_SURFACE_TYPE, EGL_WINDOW_BIT];
+        list.extend_from_slice(attribs);
+        let list = terminate(&list);
+
+        unsafe {
+            let mut count = 0;
+
+            if eglChooseConfig(self.ptr, list.as_ptr(), ptr::null_mut(), 0, &mut count) == EGL_FALSE {
+                return Err(last_error(Operation::ChooseConfig));
+            }
+
+            let mut configs = vec![ptr::null_mut(); count.max(0) as usize];
+
+            if eglChooseConfig(self.ptr, list.as_ptr(), configs.as_mut_ptr(),
+                               count, &mut count) == EGL_FALSE {
+                return Err(last_error(Operation::ChooseConfig));
+            }
+
+            configs.truncate(count.max(0) as usize);
+
+            configs.into_iter()
+                .find(|&config| {
+                    let mut visual = 0;
+
+                    eglGetConfigAttrib(self.ptr, config, EGL_NATIVE_VISUAL_ID, &mut visual) != EGL_FALSE &&
+                        visual as u32 == format.0
+                })
+                .map(|ptr| Config { ptr })
+                .ok_or(Error::NoMatchingConfig(format))
+        }
+    }
+
+    /// Create an OpenGL ES context
+    ///
+    /// # Arguments
+    ///
+    /// config: A config chosen with ```choose_config()```
+    ///
+    /// attribs: Pairs of attributes and values for the context, without the
+    /// terminating ```EGL_NONE```
+    ///
+    /// # Returns
+    ///
+    /// The newly created Context.
+    /// If an error occurs an ```Error``` will be returned.
+    pub fn create_context(&self, config: Config, attribs: &[EGLint]) -> Result<Context<'_>> {
+        let list = terminate(attribs);
+
+        unsafe {
+            if eglBindAPI(EGL_OPENGL_ES_API) == EGL_FALSE {
+                return Err(last_error(Operation::CreateContext));
+            }
+
+            let ptr = eglCreateContext(self.ptr, config.ptr, ptr::null_mut(), list.as_ptr());
+
+            if ptr.is_null() {
+                return Err(last_error(Operation::CreateContext));
+            }
+
+            Ok(Context { ptr, display: self })
+        }
+    }
+
+    /// Create a window surface rendering to a Surface
+    ///
+    /// # Arguments
+    ///
+    /// config: A config chosen with ```choose_config()``` for the format of the Surface
+    ///
+    /// surface: A Surface created from the Device of the Display
+    ///
+    /// # Returns
+    ///
+    /// The newly created WindowSurface.
+    /// If the Surface is from another Device ```Error::DeviceMismatch``` will
+    /// be returned, and if EGL fails another ```Error```.
+    pub fn create_window_surface<'a>(&'a self, config: Config,
+                                     surface: &'a Surface<'a>) -> Result<WindowSurface<'a>> {
+        if !ptr::eq(surface.device(), self.dev) {
+            return Err(Error::DeviceMismatch);
+        }
+
+        unsafe {
+            let ptr = eglCreatePlatformWindowSurface(self.ptr, config.ptr,
+                                                     surface.c_struct() as *mut c_void,
+                                                     ptr::null());
+
+            if ptr.is_null() {
+                return Err(last_error(Operation::CreateWindowSurface));
+            }
+
+            Ok(WindowSurface { ptr, display: self, surface })
+        }
+    }
+
+    /// Get the Device used to create the Display
+    ///
+    /// # Returns
+    ///
+    /// The Device with which the Display was created
+    pub fn device(&self) -> &'dev Device {
+        self.dev
+    }
+
+    /// Returns the EGLDisplay for the Display
+    ///
+    /// # Returns
+    ///
+    /// The EGLDisplay, for EGL functions the crate doesn't wrap
+    pub fn c_struct(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl<'dev> Drop for Display<'dev> {
+    fn drop(&mut self) {
+        unsafe {
+            // eglTerminate() leaves the context of this thread current
+            if eglGetCurrentDisplay() == self.ptr {
+                eglMakeCurrent(self.ptr, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            }
+
+            eglTerminate(self.ptr);
+        }
+
+        self.dev.egl_display.set(false);
+    }
+}
+
+/// Analogous to EGLConfig
+///
+/// Chosen with ```Display::choose_config()```, and only valid for that Display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    ptr: EGLConfig,
+}
+
+impl Config {
+    /// Returns the EGLConfig for the Config
+    ///
+    /// # Returns
+    ///
+    /// The EGLConfig, for EGL functions the crate doesn't wrap
+    pub fn c_struct(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+/// Analogous to EGLContext
+///
+/// An OpenGL ES context, destroyed when dropped.
+pub struct Context<'d> {
+    ptr: EGLContext,
+    display: &'d Display<'d>,
+}
+
+impl<'d> Context<'d> {
+    /// Make the context current on this thread, rendering to a WindowSurface
+    ///
+    /// # Arguments
+    ///
+    /// surface: The WindowSurface to draw to and read from
+    ///
+    /// # Returns
+    ///
+    /// An ```Error``` if the context couldn't be made current
+    pub fn make_current(&self, surface: &WindowSurface) -> Result<()> {
+        unsafe {
+            if eglMakeCurrent(self.display.ptr, surface.ptr, surface.ptr, self.ptr) == EGL_FALSE {
+                return Err(last_error(Operation::MakeCurrent));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the EGLContext for the Context
+    ///
+    /// # Returns
+    ///
+    /// The EGLContext, for EGL functions the crate doesn't wrap
+    pub fn c_struct(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl<'d> Drop for Context<'d> {
+    fn drop(&mut self) {
+        unsafe { eglDestroyContext(self.display.ptr, self.ptr); }
+    }
+}
+
+/// Analogous to EGLSurface
+///
+/// An EGL window surface rendering to the buffers of a Surface, destroyed
+/// when dropped. It borrows the Surface, so the Surface can't be destroyed
+/// while EGL still renders to it.
+pub struct WindowSurface<'a> {
+    ptr: EGLSurface,
+    display: &'a Display<'a>,
+    surface: &'a Surface<'a>,
+}
+
+impl<'a> WindowSurface<'a> {
+    /// Swap the buffers and lock the new front buffer
+    ///
+    /// The rendered frame becomes the front buffer of the Surface, which is
+    /// locked as with ```Surface::lock_front_buffer()```. Every swap needs a
+    /// free buffer to render the next frame to, so buffers must be released
+    /// once they are no longer displayed.
+    ///
+    /// # Returns
+    ///
+    /// The locked front buffer.
+    /// If swapping or locking failed an ```Error``` will be returned.
+    pub fn swap_buffers(&self) -> Result<LockedBuffer<'a>> {
+        unsafe {
+            if eglSwapBuffers(self.display.ptr, self.ptr) == EGL_FALSE {
+                return Err(last_error(Operation::SwapBuffers));
+            }
+        }
+
+        self.surface.lock_front_buffer()
+    }
+
+    /// Get the Surface rendered to
+    ///
+    /// # Returns
+    ///
+    /// The Surface the WindowSurface was created with
+    pub fn surface(&self) -> &'a Surface<'a> {
+        self.surface
+    }
+
+    /// Returns the EGLSurface for the WindowSurface
+    ///
+    /// # Returns
+    ///
+    /// The EGLSurface, for EGL functions the crate doesn't wrap
+    pub fn c_struct(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+impl<'a> Drop for WindowSurface<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            // EGL defers destroying a current surface, and would flush it
+            // after the Surface it renders to has been destroyed
+            if eglGetCurrentSurface(EGL_DRAW) == self.ptr ||
+                eglGetCurrentSurface(EGL_READ) == self.ptr {
+                eglMakeCurrent(self.display.ptr, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            }
+
+            eglDestroySurface(self.display.ptr, self.ptr);
+        }
+    }
+}
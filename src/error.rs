@@ -0,0 +1,240 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Errors returned by the fallible functions of this crate.
+
+use libc;
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+#[cfg(feature = "egl")]
+use format::Format;
+
+/// The result type returned by the fallible functions of this crate
+pub type Result<T> = result::Result<T, Error>;
+
+/// An error returned by this crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A libgbm function failed
+    ///
+    /// ```errno``` is ```None``` if libgbm did not report a reason.
+    Failed {
+        /// The operation that failed
+        op: Operation,
+        /// The errno set by libgbm
+        errno: Option<i32>,
+    },
+    /// A multi-plane import was given no planes, or more than libgbm supports
+    InvalidPlaneCount(usize),
+    /// libgbm could not be loaded at runtime, with the reason given by the
+    /// dynamic loader
+    LibraryUnavailable(&'static str),
+    /// A write was given more data than fits in the buffer object
+    InvalidWriteSize {
+        /// The number of bytes given
+        len: usize,
+        /// The number of bytes the buffer object holds
+        size: usize,
+    },
+    /// An EGL function failed
+    #[cfg(feature = "egl")]
+    Egl {
+        /// The operation that failed
+        op: Operation,
+        /// The error code returned by ```eglGetError()```
+        code: i32,
+    },
+    /// No EGL config has the format of the Surface as its native visual
+    #[cfg(feature = "egl")]
+    NoMatchingConfig(Format),
+    /// The Device already has an EGL Display
+    #[cfg(feature = "egl")]
+    DisplayInUse,
+    /// The Surface was created from a different Device than the EGL Display
+    #[cfg(feature = "egl")]
+    DeviceMismatch,
+}
+
+impl Error {
+    /// Build an error for a failed libgbm call from the current errno
+    pub(crate) fn last(op: Operation) -> Error {
+        let errno = io::Error::last_os_error().raw_os_error().filter(|&e| e != 0);
+
+        Error::Failed { op, errno }
+    }
+
+    /// Build an error for an operation the loaded libgbm doesn't provide
+    pub(crate) fn unsupported(op: Operation) -> Error {
+        Error::Failed { op, errno: Some(libc::ENOSYS) }
+    }
+
+    /// Build an error for a failed operation from an io::Error
+    pub(crate) fn from_io(op: Operation, err: &io::Error) -> Error {
+        Error::Failed { op, errno: err.raw_os_error() }
+    }
+
+    /// Get the operation that failed
+    ///
+    /// # Returns
+    ///
+    /// The failing operation, or ```None``` if the error was not caused by a
+    /// libgbm or EGL call
+    pub fn op(&self) -> Option<Operation> {
+        match *self {
+            Error::Failed { op, .. } => Some(op),
+            #[cfg(feature = "egl")]
+            Error::Egl { op, .. } => Some(op),
+            _ => None,
+        }
+    }
+
+    /// Get the errno describing the error
+    ///
+    /// # Returns
+    ///
+    /// The errno set by libgbm, or ```None``` if no reason was reported
+    pub fn errno(&self) -> Option<i32> {
+        match *self {
+            Error::Failed { errno, .. } => errno,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Failed { op, errno: Some(errno) } => {
+                write!(f, "failed to {}: {}", op, io::Error::from_raw_os_error(errno))
+            }
+            Error::Failed { op, errno: None } => write!(f, "failed to {}", op),
+            Error::InvalidPlaneCount(count) => {
+                write!(f, "invalid number of planes for import: {}", count)
+            }
+            Error::LibraryUnavailable(reason) => write!(f, "failed to load libgbm: {}", reason),
+            Error::InvalidWriteSize { len, size } => {
+                write!(f, "write of {} bytes exceeds buffer size of {} bytes", len, size)
+            }
+            #[cfg(feature = "egl")]
+            Error::Egl { op, code } => write!(f, "failed to {}: EGL error {:#06x}", op, code),
+            #[cfg(feature = "egl")]
+            Error::NoMatchingConfig(format) => {
+                write!(f, "no EGL config matches format {}", format)
+            }
+            #[cfg(feature = "egl")]
+            Error::DisplayInUse => f.write_str("the device already has an EGL display"),
+            #[cfg(feature = "egl")]
+            Error::DeviceMismatch => {
+                f.write_str("the surface was created from a different device than the EGL display")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err.errno() {
+            Some(errno) => io::Error::from_raw_os_error(errno),
+            None => io::Error::other(err),
+        }
+    }
+}
+
+/// A fallible operation performed through libgbm or EGL
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Opening the device node
+    OpenDevice,
+    /// ```gbm_create_device```
+    CreateDevice,
+    /// ```gbm_surface_create``` and its variants
+    CreateSurface,
+    /// ```gbm_surface_lock_front_buffer```
+    LockFrontBuffer,
+    /// ```gbm_bo_create``` and its variants
+    CreateBufferObject,
+    /// ```gbm_bo_import```
+    ImportBufferObject,
+    /// ```gbm_bo_get_fd``` and its variants
+    ExportFd,
+    /// ```gbm_bo_map```
+    Map,
+    /// ```gbm_bo_write```
+    Write,
+    /// ```eglGetPlatformDisplay``` and ```eglInitialize```
+    #[cfg(feature = "egl")]
+    GetDisplay,
+    /// ```eglChooseConfig```
+    #[cfg(feature = "egl")]
+    ChooseConfig,
+    /// ```eglCreatePlatformWindowSurface```
+    #[cfg(feature = "egl")]
+    CreateWindowSurface,
+    /// ```eglCreateContext```
+    #[cfg(feature = "egl")]
+    CreateContext,
+    /// ```eglMakeCurrent```
+    #[cfg(feature = "egl")]
+    MakeCurrent,
+    /// ```eglSwapBuffers```
+    #[cfg(feature = "egl")]
+    SwapBuffers,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Operation::OpenDevice => "open device",
+            Operation::CreateDevice => "create device",
+            Operation::CreateSurface => "create surface",
+            Operation::LockFrontBuffer => "lock front buffer",
+            Operation::CreateBufferObject => "create buffer object",
+            Operation::ImportBufferObject => "import buffer object",
+            Operation::ExportFd => "export buffer object fd",
+            Operation::Map => "map buffer object",
+            Operation::Write => "write buffer object",
+            #[cfg(feature = "egl")]
+            Operation::GetDisplay => "get EGL display",
+            #[cfg(feature = "egl")]
+            Operation::ChooseConfig => "choose EGL config",
+            #[cfg(feature = "egl")]
+            Operation::CreateWindowSurface => "create EGL window surface",
+            #[cfg(feature = "egl")]
+            Operation::CreateContext => "create EGL context",
+            #[cfg(feature = "egl")]
+            Operation::MakeCurrent => "make EGL context current",
+            #[cfg(feature = "egl")]
+            Operation::SwapBuffers => "swap buffers",
+        })
+    }
+}
+
+/// Reset errno so that a failing call that doesn't set it isn't blamed on
+/// an earlier one
+pub(crate) fn clear_errno() {
+    unsafe { *libc::__errno_location() = 0 }
+}
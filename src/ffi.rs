@@ -0,0 +1,303 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! The table of libgbm functions used by the crate.
+//!
+//! By default libgbm is linked when the crate is built. With the ```dlopen```
+//! feature it is instead loaded when the first Device is created. Either way
+//! only the functions every version of libgbm has are required, the ones that
+//! older versions lack are looked up at runtime and left out of the table.
+
+use libc::{c_char, c_int, c_uint, c_void, size_t};
+use std::fmt;
+
+use std::ffi::CStr;
+use std::sync::OnceLock;
+use std::{mem, result};
+
+use error::Result;
+use {gbm_bo, gbm_device, gbm_surface};
+
+#[cfg(feature = "dlopen")]
+use error::Error;
+
+macro_rules! api {
+    (
+        required {
+            $(fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)*;)*
+        }
+        optional {
+            $(fn $opt_name:ident($($opt_arg:ident: $opt_ty:ty),*) $(-> $opt_ret:ty)*;)*
+        }
+    ) => {
+        /// The libgbm functions used by the crate
+        ///
+        /// Functions missing from older versions of libgbm are ```None```.
+        #[allow(non_snake_case)]
+        pub(crate) struct Api {
+            $(pub $name: unsafe extern "C" fn($($arg: $ty),*) $(-> $ret)*,)*
+            $(pub $opt_name: Option<unsafe extern "C" fn($($opt_arg: $opt_ty),*) $(-> $opt_ret)*>,)*
+        }
+
+        #[cfg(not(feature = "dlopen"))]
+        mod linked {
+            use super::*;
+
+            // The optional functions aren't linked, so older versions of
+            // libgbm can still be loaded
+            #[link(name = "gbm")]
+            extern "C" {
+                $(pub fn $name($($arg: $ty),*) $(-> $ret)*;)*
+            }
+        }
+
+        #[cfg(not(feature = "dlopen"))]
+        impl Api {
+            /// Use the linked functions, looking up the optional ones in the
+            /// libgbm that was loaded with the program
+            unsafe fn linked() -> Api {
+                Api {
+                    $($name: linked::$name,)*
+                    $($opt_name: symbol(libc::RTLD_DEFAULT, concat!(stringify!($opt_name), "\0"))
+                        .ok()
+                        .map(|sym| mem::transmute::<*mut c_void, unsafe extern "C" fn($($opt_ty),*) $(-> $opt_ret)*>(sym)),)*
+                }
+            }
+        }
+
+        #[cfg(feature = "dlopen")]
+        impl Api {
+            /// Resolve the functions from a handle returned by ```dlopen()```
+            unsafe fn load(handle: *mut c_void) -> result::Result<Api, String> {
+                Ok(Api {
+                    $($name: mem::transmute::<*mut c_void, unsafe extern "C" fn($($ty),*) $(-> $ret)*>(
+                        symbol(handle, concat!(stringify!($name), "\0"))?),)*
+                    $($opt_name: symbol(handle, concat!(stringify!($opt_name), "\0"))
+                        .ok()
+                        .map(|sym| mem::transmute::<*mut c_void, unsafe extern "C" fn($($opt_ty),*) $(-> $opt_ret)*>(sym)),)*
+                })
+            }
+        }
+    }
+}
+
+api! {
+    required {
+        // Device keeps its own OwnedFd instead
+        // fn gbm_device_get_fd(gbm: *const gbm_device) -> c_int;
+        fn gbm_device_get_backend_name(gbm: *const gbm_device) -> *const c_char;
+        fn gbm_device_is_format_supported(gbm: *const gbm_device,
+                                          format: u32, usage: u32) -> c_int;
+        fn gbm_device_destroy(gbm: *const gbm_device);
+        fn gbm_create_device(fd: c_int) -> *const gbm_device;
+        fn gbm_bo_create(gbm: *const gbm_device,
+                         width: u32, height: u32,
+                         format: u32, flags: u32) -> *const gbm_bo;
+        fn gbm_bo_import(gbm: *const gbm_device, _type: u32,
+                         buffer: *const c_void, usage: u32) -> *const gbm_bo;
+        fn gbm_bo_get_width(bo: *const gbm_bo) -> u32;
+        fn gbm_bo_get_height(bo: *const gbm_bo) -> u32;
+        fn gbm_bo_get_stride(bo: *const gbm_bo) -> u32;
+        fn gbm_bo_get_format(bo: *const gbm_bo) -> u32;
+        // BufferObject keeps a reference to its Device instead
+        // fn gbm_bo_get_device(bo: *const gbm_bo) -> *const gbm_device;
+        fn gbm_bo_get_handle(bo: *const gbm_bo) -> u64;
+        fn gbm_bo_get_fd(bo: *const gbm_bo) -> c_int;
+        fn gbm_bo_write(bo: *const gbm_bo, buf: *const c_void, count: size_t) -> c_int;
+        fn gbm_bo_set_user_data(bo: *const gbm_bo, data: *mut c_void,
+                                destroy_user_data: Option<extern "C" fn(bo: *const gbm_bo, data: *mut c_void)>);
+        fn gbm_bo_get_user_data(bo: *const gbm_bo) -> *mut c_void;
+        fn gbm_bo_destroy(bo: *const gbm_bo);
+        fn gbm_surface_create(gbm: *const gbm_device,
+                              width: u32, height: u32,
+                              format: u32, flags: u32) -> *const gbm_surface;
+        // This function doesn't seem to have actually been implemented
+        // fn gbm_surface_needs_lock_front_buffer(surface: *const gbm_surface) -> c_int;
+        fn gbm_surface_lock_front_buffer(surface: *const gbm_surface) -> *const gbm_bo;
+        fn gbm_surface_release_buffer(surface: *const gbm_surface, bo: *const gbm_bo);
+        fn gbm_surface_has_free_buffers(surface: *const gbm_surface) -> c_int;
+        fn gbm_surface_destroy(surface: *const gbm_surface);
+    }
+    optional {
+        fn gbm_device_get_format_modifier_plane_count(gbm: *const gbm_device,
+                                                      format: u32, modifier: u64) -> c_int;
+        fn gbm_bo_create_with_modifiers(gbm: *const gbm_device,
+                                        width: u32, height: u32, format: u32,
+                                        modifiers: *const u64, count: c_uint) -> *const gbm_bo;
+        fn gbm_bo_create_with_modifiers2(gbm: *const gbm_device,
+                                         width: u32, height: u32, format: u32,
+                                         modifiers: *const u64, count: c_uint,
+                                         flags: u32) -> *const gbm_bo;
+        fn gbm_bo_get_modifier(bo: *const gbm_bo) -> u64;
+        fn gbm_bo_get_plane_count(bo: *const gbm_bo) -> c_int;
+        fn gbm_bo_get_offset(bo: *const gbm_bo, plane: c_int) -> u32;
+        fn gbm_bo_get_stride_for_plane(bo: *const gbm_bo, plane: c_int) -> u32;
+        fn gbm_bo_get_handle_for_plane(bo: *const gbm_bo, plane: c_int) -> u64;
+        fn gbm_bo_get_fd_for_plane(bo: *const gbm_bo, plane: c_int) -> c_int;
+        fn gbm_bo_get_bpp(bo: *const gbm_bo) -> u32;
+        fn gbm_bo_map(bo: *const gbm_bo, x: u32, y: u32, width: u32, height: u32,
+                      flags: u32, stride: *mut u32, map_data: *mut *mut c_void) -> *mut c_void;
+        fn gbm_bo_unmap(bo: *const gbm_bo, map_data: *mut c_void);
+        fn gbm_surface_create_with_modifiers(gbm: *const gbm_device,
+                                             width: u32, height: u32, format: u32,
+                                             modifiers: *const u64, count: c_uint) -> *const gbm_surface;
+        fn gbm_surface_create_with_modifiers2(gbm: *const gbm_device,
+                                              width: u32, height: u32, format: u32,
+                                              modifiers: *const u64, count: c_uint,
+                                              flags: u32) -> *const gbm_surface;
+    }
+}
+
+impl Api {
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_with_modifiers: self.gbm_bo_create_with_modifiers.is_some() &&
+                self.gbm_surface_create_with_modifiers.is_some(),
+            create_with_modifiers2: self.gbm_bo_create_with_modifiers2.is_some() &&
+                self.gbm_surface_create_with_modifiers2.is_some(),
+            bo_modifier: self.gbm_bo_get_modifier.is_some(),
+            planes: self.gbm_bo_get_plane_count.is_some() &&
+                self.gbm_bo_get_offset.is_some() &&
+                self.gbm_bo_get_stride_for_plane.is_some() &&
+                self.gbm_bo_get_handle_for_plane.is_some(),
+            fd_for_plane: self.gbm_bo_get_fd_for_plane.is_some(),
+            format_modifier_plane_count: self.gbm_device_get_format_modifier_plane_count.is_some(),
+            map: self.gbm_bo_map.is_some() &&
+                self.gbm_bo_unmap.is_some() &&
+                self.gbm_bo_get_bpp.is_some(),
+        }
+    }
+}
+
+// The table only holds function pointers
+impl fmt::Debug for Api {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Api").field("capabilities", &self.capabilities()).finish()
+    }
+}
+
+/// The optional parts of libgbm that are available
+///
+/// Different versions of libgbm export different functions. Where one is
+/// missing the crate falls back to an older equivalent when there is one,
+/// and otherwise fails with ```ENOSYS```.
+///
+/// This is the same with and without the ```dlopen``` feature, only the
+/// functions every version of libgbm has are linked at build time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// ```gbm_bo_create_with_modifiers()``` and
+    /// ```gbm_surface_create_with_modifiers()```, which ignore the usage flags
+    pub create_with_modifiers: bool,
+    /// ```gbm_bo_create_with_modifiers2()``` and
+    /// ```gbm_surface_create_with_modifiers2()```, from Mesa 21.3
+    pub create_with_modifiers2: bool,
+    /// ```gbm_bo_get_modifier()```, without it every buffer object reports
+    /// ```Modifier::INVALID```
+    pub bo_modifier: bool,
+    /// Querying the offset, stride and handle of each plane of a buffer
+    /// object, without it every buffer object reports a single plane
+    pub planes: bool,
+    /// ```gbm_bo_get_fd_for_plane()```, from Mesa 21.1, without it every
+    /// plane is exported with the fd of the whole buffer object
+    pub fd_for_plane: bool,
+    /// ```gbm_device_get_format_modifier_plane_count()```
+    pub format_modifier_plane_count: bool,
+    /// Mapping buffer objects for CPU access
+    pub map: bool,
+}
+
+/// Get the table of libgbm functions, loading libgbm if needed
+#[cfg(not(feature = "dlopen"))]
+pub(crate) fn api() -> Result<&'static Api> {
+    static API: OnceLock<Api> = OnceLock::new();
+
+    Ok(API.get_or_init(|| unsafe { Api::linked() }))
+}
+
+/// Get the table of libgbm functions, loading libgbm if needed
+#[cfg(feature = "dlopen")]
+pub(crate) fn api() -> Result<&'static Api> {
+    static API: OnceLock<result::Result<Api, String>> = OnceLock::new();
+
+    let api = API.get_or_init(|| unsafe {
+        let handle = libc::dlopen(b"libgbm.so.1\0".as_ptr() as *const _,
+                                  libc::RTLD_NOW | libc::RTLD_LOCAL);
+
+        if handle.is_null() {
+            return Err(dlerror());
+        }
+
+        // The library is never unloaded, the table lives as long as the process
+        Api::load(handle)
+    });
+
+    match *api {
+        Ok(ref api) => Ok(api),
+        Err(ref reason) => Err(Error::LibraryUnavailable(reason)),
+    }
+}
+
+unsafe fn symbol(handle: *mut c_void, name: &str) -> result::Result<*mut c_void, String> {
+    // Clear any earlier error so a missing symbol isn't confused with it
+    libc::dlerror();
+
+    let sym = libc::dlsym(handle, name.as_ptr() as *const _);
+
+    if sym.is_null() {
+        return Err(dlerror());
+    }
+
+    Ok(sym)
+}
+
+unsafe fn dlerror() -> String {
+    let reason = libc::dlerror();
+
+    if reason.is_null() {
+        return String::from("unknown error");
+    }
+
+    CStr::from_ptr(reason).to_string_lossy().into_owned()
+}
+
+/// Load libgbm and report which of its optional parts are available
+///
+/// With the ```dlopen``` feature libgbm.so.1 is loaded the first time this
+/// function, or a Device constructor, is called. It is never unloaded.
+///
+/// # Returns
+///
+/// The optional parts of libgbm that can be used, or
+/// ```Error::LibraryUnavailable``` if libgbm could not be loaded
+///
+/// # Example
+/// ```
+/// extern crate gbm_rs as gbm;
+///
+/// match gbm::load() {
+///     Ok(caps) => println!("libgbm can map buffers: {}", caps.map),
+///     Err(err) => println!("{}", err),
+/// }
+/// ```
+pub fn load() -> Result<Capabilities> {
+    api().map(Api::capabilities)
+}
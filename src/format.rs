@@ -0,0 +1,768 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Pixel formats, identified by their fourcc code.
+//!
+//! The constants in this module mirror the ```GBM_FORMAT_*``` and
+//! ```DRM_FORMAT_*``` definitions.
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A pixel format, identified by its fourcc code
+///
+/// Displays as the four-character code, e.g. ```XR24``` for ```XRGB8888```.
+/// Can be parsed from either its name or its four-character code.
+///
+/// # Example
+/// ```
+/// extern crate gbm_rs as gbm;
+///
+/// use gbm::format::{self, Format};
+///
+/// assert_eq!(format::XRGB8888.to_string(), "XR24");
+/// assert_eq!("XRGB8888".parse::<Format>(), Ok(format::XRGB8888));
+/// assert_eq!("XR24".parse::<Format>(), Ok(format::XRGB8888));
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Format(pub u32);
+
+impl Format {
+    /// Get the name of the format
+    ///
+    /// # Returns
+    ///
+    /// The name of the constant in this module for the format, such as
+    /// ```XRGB8888```, or ```None``` if the format is unknown
+    pub fn name(&self) -> Option<&'static str> {
+        NAMES.iter()
+            .find(|&&(_, format)| format == *self)
+            .map(|&(name, _)| name)
+    }
+
+    /// Get the four-character code of the format
+    ///
+    /// # Returns
+    ///
+    /// The four bytes making up the fourcc code, in order, without the
+    /// ```BIG_ENDIAN``` flag
+    pub fn fourcc(&self) -> [u8; 4] {
+        (self.0 & !BIG_ENDIAN).to_le_bytes()
+    }
+
+    /// Test if the format has the ```BIG_ENDIAN``` flag set
+    ///
+    /// # Returns
+    ///
+    /// true if the components are stored big endian otherwise false
+    pub fn is_big_endian(&self) -> bool {
+        self.0 & BIG_ENDIAN != 0
+    }
+
+    /// Get the big endian variant of the format
+    ///
+    /// # Returns
+    ///
+    /// The format with the ```BIG_ENDIAN``` flag set
+    pub fn big_endian(self) -> Format {
+        Format(self.0 | BIG_ENDIAN)
+    }
+
+    /// Get the little endian variant of the format
+    ///
+    /// # Returns
+    ///
+    /// The format with the ```BIG_ENDIAN``` flag cleared
+    pub fn little_endian(self) -> Format {
+        Format(self.0 & !BIG_ENDIAN)
+    }
+
+    /// Get the layout metadata of the format
+    ///
+    /// See ```format::info()```.
+    pub fn info(&self) -> Option<FormatInfo> {
+        info(*self)
+    }
+}
+
+impl From<u32> for Format {
+    fn from(format: u32) -> Format {
+        Format(format)
+    }
+}
+
+impl From<Format> for u32 {
+    fn from(format: Format) -> u32 {
+        format.0
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let code = self.fourcc();
+
+        if !code.iter().all(|&c| c == b' ' || c.is_ascii_graphic()) {
+            return write!(f, "{:#010x}", self.0);
+        }
+
+        let code: String = code.iter().map(|&c| c as char).collect();
+        f.write_str(code.trim_end())?;
+
+        if self.is_big_endian() {
+            f.write_str(" big-endian")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.little_endian().name(), self.is_big_endian()) {
+            (Some(name), false) => write!(f, "Format({})", name),
+            (Some(name), true) => write!(f, "Format({} | BIG_ENDIAN)", name),
+            (None, _) => write!(f, "Format({})", self),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Format, ParseFormatError> {
+        if let Some(&(_, format)) = NAMES.iter().find(|&&(name, _)| name == s) {
+            return Ok(format);
+        }
+
+        // Short codes such as "C8" are padded with spaces
+        let code = s.as_bytes();
+        if code.is_empty() || code.len() > 4 || !code.iter().all(|c| c.is_ascii_graphic()) {
+            return Err(ParseFormatError(()));
+        }
+
+        let mut fourcc = [b' '; 4];
+        fourcc[..code.len()].copy_from_slice(code);
+
+        Ok(Format(u32::from_le_bytes(fourcc)))
+    }
+}
+
+/// The error returned when parsing a Format fails
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseFormatError(());
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("not a known format name or a fourcc code")
+    }
+}
+
+impl error::Error for ParseFormatError {}
+
+/// How the components of a format describe a color
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorModel {
+    /// Each pixel is an index into a color palette
+    Indexed,
+    /// Red, green and blue components
+    Rgb,
+    /// Luma and chroma components
+    Yuv,
+}
+
+/// Layout metadata of a format
+///
+/// Returned by ```format::info()```. Chroma subsampling only applies to planes
+/// after the first; the first plane is always sampled at full resolution.
+///
+/// # Example
+/// ```
+/// extern crate gbm_rs as gbm;
+///
+/// use gbm::format;
+///
+/// let info = format::info(format::NV12).unwrap();
+///
+/// assert_eq!(info.planes, 2);
+/// assert_eq!(info.min_stride(1, 1920), Some(1920));
+/// assert_eq!(info.plane_height(1, 1080), 540);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FormatInfo {
+    /// The format described
+    pub format: Format,
+    /// The color model of the format
+    pub color_model: ColorModel,
+    /// The number of planes
+    pub planes: u32,
+    /// The number of bytes per pixel of each plane
+    pub cpp: [u8; 4],
+    /// The horizontal chroma subsampling factor
+    pub hsub: u32,
+    /// The vertical chroma subsampling factor
+    pub vsub: u32,
+    /// Whether the format has an alpha component
+    pub has_alpha: bool,
+    /// The format with the alpha component replaced by padding, if there is one
+    pub opaque: Option<Format>,
+}
+
+impl FormatInfo {
+    /// Get the width of a plane
+    ///
+    /// # Arguments
+    ///
+    /// plane: The index of the plane
+    ///
+    /// width: The width of the buffer in pixels
+    ///
+    /// # Returns
+    ///
+    /// The width of the plane in pixels
+    pub fn plane_width(&self, plane: usize, width: u32) -> u32 {
+        match plane {
+            0 => width,
+            _ => width.div_ceil(self.hsub),
+        }
+    }
+
+    /// Get the height of a plane
+    ///
+    /// # Arguments
+    ///
+    /// plane: The index of the plane
+    ///
+    /// height: The height of the buffer in pixels
+    ///
+    /// # Returns
+    ///
+    /// The height of the plane in rows
+    pub fn plane_height(&self, plane: usize, height: u32) -> u32 {
+        match plane {
+            0 => height,
+            _ => height.div_ceil(self.vsub),
+        }
+    }
+
+    /// Get the smallest stride a plane can have
+    ///
+    /// # Arguments
+    ///
+    /// plane: The index of the plane
+    ///
+    /// width: The width of the buffer in pixels
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes needed to store a row of the plane without padding,
+    /// or ```None``` if it doesn't fit in a u32
+    pub fn min_stride(&self, plane: usize, width: u32) -> Option<u32> {
+        self.plane_width(plane, width).checked_mul(self.cpp[plane] as u32)
+    }
+
+    const fn packed(color_model: ColorModel, cpp: u8) -> FormatInfo {
+        FormatInfo {
+            format: Format(0),
+            color_model,
+            planes: 1,
+            cpp: [cpp, 0, 0, 0],
+            hsub: 1,
+            vsub: 1,
+            has_alpha: false,
+            opaque: None,
+        }
+    }
+
+    const fn planar(color_model: ColorModel, cpp: [u8; 4], hsub: u32, vsub: u32) -> FormatInfo {
+        let mut planes = 0;
+        while planes < cpp.len() && cpp[planes] != 0 {
+            planes += 1;
+        }
+
+        FormatInfo {
+            format: Format(0),
+            color_model,
+            planes: planes as u32,
+            cpp,
+            hsub,
+            vsub,
+            has_alpha: false,
+            opaque: None,
+        }
+    }
+
+    const fn subsampled(self, hsub: u32, vsub: u32) -> FormatInfo {
+        FormatInfo { hsub, vsub, ..self }
+    }
+
+    const fn alpha(self, opaque: Option<Format>) -> FormatInfo {
+        FormatInfo { has_alpha: true, opaque, ..self }
+    }
+}
+
+/// Look up the layout metadata of a format
+///
+/// # Arguments
+///
+/// format: The format to look up
+///
+/// # Returns
+///
+/// The metadata of the format, or ```None``` if the format is not one of the
+/// constants in this module, optionally with the ```BIG_ENDIAN``` flag set
+pub fn info(format: Format) -> Option<FormatInfo> {
+    use self::ColorModel::*;
+
+    // Byte order doesn't change the layout
+    let info = match format.little_endian() {
+        C8 => FormatInfo::packed(Indexed, 1),
+
+        R8 => FormatInfo::packed(Rgb, 1),
+        R10 | R12 | R16 | RG88 | GR88 => FormatInfo::packed(Rgb, 2),
+        RG1616 | GR1616 => FormatInfo::packed(Rgb, 4),
+
+        RGB332 | BGR233 => FormatInfo::packed(Rgb, 1),
+
+        XRGB4444 | XBGR4444 | RGBX4444 | BGRX4444 |
+        XRGB1555 | XBGR1555 | RGBX5551 | BGRX5551 |
+        RGB565 | BGR565 => FormatInfo::packed(Rgb, 2),
+        ARGB4444 => FormatInfo::packed(Rgb, 2).alpha(Some(XRGB4444)),
+        ABGR4444 => FormatInfo::packed(Rgb, 2).alpha(Some(XBGR4444)),
+        RGBA4444 => FormatInfo::packed(Rgb, 2).alpha(Some(RGBX4444)),
+        BGRA4444 => FormatInfo::packed(Rgb, 2).alpha(Some(BGRX4444)),
+        ARGB1555 => FormatInfo::packed(Rgb, 2).alpha(Some(XRGB1555)),
+        ABGR1555 => FormatInfo::packed(Rgb, 2).alpha(Some(XBGR1555)),
+        RGBA5551 => FormatInfo::packed(Rgb, 2).alpha(Some(RGBX5551)),
+        BGRA5551 => FormatInfo::packed(Rgb, 2).alpha(Some(BGRX5551)),
+
+        RGB888 | BGR888 => FormatInfo::packed(Rgb, 3),
+
+        XRGB8888 | XBGR8888 | RGBX8888 | BGRX8888 |
+        XRGB2101010 | XBGR2101010 | RGBX1010102 | BGRX1010102 => FormatInfo::packed(Rgb, 4),
+        ARGB8888 => FormatInfo::packed(Rgb, 4).alpha(Some(XRGB8888)),
+        ABGR8888 => FormatInfo::packed(Rgb, 4).alpha(Some(XBGR8888)),
+        RGBA8888 => FormatInfo::packed(Rgb, 4).alpha(Some(RGBX8888)),
+        BGRA8888 => FormatInfo::packed(Rgb, 4).alpha(Some(BGRX8888)),
+        ARGB2101010 => FormatInfo::packed(Rgb, 4).alpha(Some(XRGB2101010)),
+        ABGR2101010 => FormatInfo::packed(Rgb, 4).alpha(Some(XBGR2101010)),
+        RGBA1010102 => FormatInfo::packed(Rgb, 4).alpha(Some(RGBX1010102)),
+        BGRA1010102 => FormatInfo::packed(Rgb, 4).alpha(Some(BGRX1010102)),
+
+        XRGB16161616 | XBGR16161616 |
+        XRGB16161616F | XBGR16161616F => FormatInfo::packed(Rgb, 8),
+        ARGB16161616 => FormatInfo::packed(Rgb, 8).alpha(Some(XRGB16161616)),
+        ABGR16161616 => FormatInfo::packed(Rgb, 8).alpha(Some(XBGR16161616)),
+        ARGB16161616F => FormatInfo::packed(Rgb, 8).alpha(Some(XRGB16161616F)),
+        ABGR16161616F => FormatInfo::packed(Rgb, 8).alpha(Some(XBGR16161616F)),
+        AXBXGXRX106106106106 => FormatInfo::packed(Rgb, 8).alpha(None),
+
+        XRGB8888_A8 => FormatInfo::planar(Rgb, [4, 1, 0, 0], 1, 1).alpha(Some(XRGB8888)),
+        XBGR8888_A8 => FormatInfo::planar(Rgb, [4, 1, 0, 0], 1, 1).alpha(Some(XBGR8888)),
+        RGBX8888_A8 => FormatInfo::planar(Rgb, [4, 1, 0, 0], 1, 1).alpha(Some(RGBX8888)),
+        BGRX8888_A8 => FormatInfo::planar(Rgb, [4, 1, 0, 0], 1, 1).alpha(Some(BGRX8888)),
+        RGB888_A8 => FormatInfo::planar(Rgb, [3, 1, 0, 0], 1, 1).alpha(Some(RGB888)),
+        BGR888_A8 => FormatInfo::planar(Rgb, [3, 1, 0, 0], 1, 1).alpha(Some(BGR888)),
+        RGB565_A8 => FormatInfo::planar(Rgb, [2, 1, 0, 0], 1, 1).alpha(Some(RGB565)),
+        BGR565_A8 => FormatInfo::planar(Rgb, [2, 1, 0, 0], 1, 1).alpha(Some(BGR565)),
+
+        YUYV | YVYU | UYVY | VYUY => FormatInfo::packed(Yuv, 2).subsampled(2, 1),
+        AYUV => FormatInfo::packed(Yuv, 4).alpha(Some(XYUV8888)),
+        AVUY8888 => FormatInfo::packed(Yuv, 4).alpha(Some(XVUY8888)),
+        XYUV8888 | XVUY8888 | XVYU2101010 => FormatInfo::packed(Yuv, 4),
+        VUY888 => FormatInfo::packed(Yuv, 3),
+        XVYU12_16161616 | XVYU16161616 => FormatInfo::packed(Yuv, 8),
+
+        Y210 | Y212 | Y216 => FormatInfo::packed(Yuv, 4).subsampled(2, 1),
+        Y410 => FormatInfo::packed(Yuv, 4).alpha(Some(XVYU2101010)),
+        Y412 => FormatInfo::packed(Yuv, 8).alpha(Some(XVYU12_16161616)),
+        Y416 => FormatInfo::packed(Yuv, 8).alpha(Some(XVYU16161616)),
+
+        NV12 | NV21 => FormatInfo::planar(Yuv, [1, 2, 0, 0], 2, 2),
+        NV16 | NV61 => FormatInfo::planar(Yuv, [1, 2, 0, 0], 2, 1),
+        NV24 | NV42 => FormatInfo::planar(Yuv, [1, 2, 0, 0], 1, 1),
+
+        P210 => FormatInfo::planar(Yuv, [2, 4, 0, 0], 2, 1),
+        P010 | P012 | P016 => FormatInfo::planar(Yuv, [2, 4, 0, 0], 2, 2),
+
+        Q410 | Q401 => FormatInfo::planar(Yuv, [2, 2, 2, 0], 1, 1),
+
+        YUV410 | YVU410 => FormatInfo::planar(Yuv, [1, 1, 1, 0], 4, 4),
+        YUV411 | YVU411 => FormatInfo::planar(Yuv, [1, 1, 1, 0], 4, 1),
+        YUV420 | YVU420 => FormatInfo::planar(Yuv, [1, 1, 1, 0], 2, 2),
+        YUV422 | YVU422 => FormatInfo::planar(Yuv, [1, 1, 1, 0], 2, 1),
+        YUV444 | YVU444 => FormatInfo::planar(Yuv, [1, 1, 1, 0], 1, 1),
+
+        _ => return None,
+    };
+
+    Some(FormatInfo { format, ..info })
+}
+
+/// A type that can be written to a buffer as the raw bytes of a pixel
+///
+/// Used by ```BufferObject::write_pixels()```, e.g. ```u32``` for an
+/// ```ARGB8888``` cursor image or ```[u8; 3]``` for ```RGB888```.
+///
+/// # Safety
+///
+/// The type must be plain data without padding bytes, so that every byte of
+/// a slice of it is initialized.
+pub unsafe trait Pixel: Copy {}
+
+macro_rules! pixels {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pixel for $ty {})*
+    }
+}
+
+pixels!(u8, u16, u32, u64, [u8; 2], [u8; 3], [u8; 4], [u16; 2], [u16; 3], [u16; 4]);
+
+/// Iterate over the formats defined in this module
+///
+/// Big endian variants are not included.
+///
+/// # Returns
+///
+/// An iterator yielding each format constant of this module
+pub fn all() -> impl Iterator<Item = Format> {
+    NAMES.iter().map(|&(_, format)| format)
+}
+
+/// Flag set on a format whose components are stored big endian instead of
+/// little endian
+pub const BIG_ENDIAN: u32 = 1 << 31;
+
+macro_rules! fourcc_code {
+    ($a:expr, $b:expr, $c:expr, $d:expr) => {
+        ($a as u32) | (($b as u32) << 8) | (($c as u32) << 16) | (($d as u32) << 24)
+    }
+}
+
+macro_rules! formats {
+    ($($(#[$attr:meta])* $name:ident = $code:expr;)*) => {
+        $(
+            $(#[$attr])*
+            pub const $name: Format = Format($code);
+        )*
+
+        const NAMES: &[(&str, Format)] = &[$((stringify!($name), $name),)*];
+    }
+}
+
+formats! {
+    // Color index
+
+    /// [7:0] C
+    C8 = fourcc_code!('C', '8', ' ', ' ');
+
+    // 8 bpp Red
+
+    /// [7:0] R
+    R8 = fourcc_code!('R', '8', ' ', ' ');
+
+    // 10 bpp Red
+
+    /// [15:0] x:R 6:10 little endian
+    R10 = fourcc_code!('R', '1', '0', ' ');
+
+    // 12 bpp Red
+
+    /// [15:0] x:R 4:12 little endian
+    R12 = fourcc_code!('R', '1', '2', ' ');
+
+    // 16 bpp Red
+
+    /// [15:0] R little endian
+    R16 = fourcc_code!('R', '1', '6', ' ');
+
+    // 16 bpp RG
+
+    /// [15:0] R:G 8:8 little endian
+    RG88 = fourcc_code!('R', 'G', '8', '8');
+    /// [15:0] G:R 8:8 little endian
+    GR88 = fourcc_code!('G', 'R', '8', '8');
+
+    // 32 bpp RG
+
+    /// [31:0] R:G 16:16 little endian
+    RG1616 = fourcc_code!('R', 'G', '3', '2');
+    /// [31:0] G:R 16:16 little endian
+    GR1616 = fourcc_code!('G', 'R', '3', '2');
+
+    // 8 bpp RGB
+
+    /// [7:0] R:G:B 3:3:2
+    RGB332 = fourcc_code!('R', 'G', 'B', '8');
+    /// [7:0] B:G:R 2:3:3
+    BGR233 = fourcc_code!('B', 'G', 'R', '8');
+
+    // 16 bpp RGB
+
+    /// [15:0] x:R:G:B 4:4:4:4 little endian
+    XRGB4444 = fourcc_code!('X', 'R', '1', '2');
+    /// [15:0] x:B:G:R 4:4:4:4 little endian
+    XBGR4444 = fourcc_code!('X', 'B', '1', '2');
+    /// [15:0] R:G:B:x 4:4:4:4 little endian
+    RGBX4444 = fourcc_code!('R', 'X', '1', '2');
+    /// [15:0] B:G:R:x 4:4:4:4 little endian
+    BGRX4444 = fourcc_code!('B', 'X', '1', '2');
+
+    /// [15:0] A:R:G:B 4:4:4:4 little endian
+    ARGB4444 = fourcc_code!('A', 'R', '1', '2');
+    /// [15:0] A:B:G:R 4:4:4:4 little endian
+    ABGR4444 = fourcc_code!('A', 'B', '1', '2');
+    /// [15:0] R:G:B:A 4:4:4:4 little endian
+    RGBA4444 = fourcc_code!('R', 'A', '1', '2');
+    /// [15:0] B:G:R:A 4:4:4:4 little endian
+    BGRA4444 = fourcc_code!('B', 'A', '1', '2');
+
+    /// [15:0] x:R:G:B 1:5:5:5 little endian
+    XRGB1555 = fourcc_code!('X', 'R', '1', '5');
+    /// [15:0] x:B:G:R 1:5:5:5 little endian
+    XBGR1555 = fourcc_code!('X', 'B', '1', '5');
+    /// [15:0] R:G:B:x 5:5:5:1 little endian
+    RGBX5551 = fourcc_code!('R', 'X', '1', '5');
+    /// [15:0] B:G:R:x 5:5:5:1 little endian
+    BGRX5551 = fourcc_code!('B', 'X', '1', '5');
+
+    /// [15:0] A:R:G:B 1:5:5:5 little endian
+    ARGB1555 = fourcc_code!('A', 'R', '1', '5');
+    /// [15:0] A:B:G:R 1:5:5:5 little endian
+    ABGR1555 = fourcc_code!('A', 'B', '1', '5');
+    /// [15:0] R:G:B:A 5:5:5:1 little endian
+    RGBA5551 = fourcc_code!('R', 'A', '1', '5');
+    /// [15:0] B:G:R:A 5:5:5:1 little endian
+    BGRA5551 = fourcc_code!('B', 'A', '1', '5');
+
+    /// [15:0] R:G:B 5:6:5 little endian
+    RGB565 = fourcc_code!('R', 'G', '1', '6');
+    /// [15:0] B:G:R 5:6:5 little endian
+    BGR565 = fourcc_code!('B', 'G', '1', '6');
+
+    // 24 bpp RGB
+
+    /// [23:0] R:G:B little endian
+    RGB888 = fourcc_code!('R', 'G', '2', '4');
+    /// [23:0] B:G:R little endian
+    BGR888 = fourcc_code!('B', 'G', '2', '4');
+
+    // 32 bpp RGB
+
+    /// [31:0] x:R:G:B 8:8:8:8 little endian
+    XRGB8888 = fourcc_code!('X', 'R', '2', '4');
+    /// [31:0] x:B:G:R 8:8:8:8 little endian
+    XBGR8888 = fourcc_code!('X', 'B', '2', '4');
+    /// [31:0] R:G:B:x 8:8:8:8 little endian
+    RGBX8888 = fourcc_code!('R', 'X', '2', '4');
+    /// [31:0] B:G:R:x 8:8:8:8 little endian
+    BGRX8888 = fourcc_code!('B', 'X', '2', '4');
+
+    /// [31:0] A:R:G:B 8:8:8:8 little endian
+    ARGB8888 = fourcc_code!('A', 'R', '2', '4');
+    /// [31:0] A:B:G:R 8:8:8:8 little endian
+    ABGR8888 = fourcc_code!('A', 'B', '2', '4');
+    /// [31:0] R:G:B:A 8:8:8:8 little endian
+    RGBA8888 = fourcc_code!('R', 'A', '2', '4');
+    /// [31:0] B:G:R:A 8:8:8:8 little endian
+    BGRA8888 = fourcc_code!('B', 'A', '2', '4');
+
+    /// [31:0] x:R:G:B 2:10:10:10 little endian
+    XRGB2101010 = fourcc_code!('X', 'R', '3', '0');
+    /// [31:0] x:B:G:R 2:10:10:10 little endian
+    XBGR2101010 = fourcc_code!('X', 'B', '3', '0');
+    /// [31:0] R:G:B:x 10:10:10:2 little endian
+    RGBX1010102 = fourcc_code!('R', 'X', '3', '0');
+    /// [31:0] B:G:R:x 10:10:10:2 little endian
+    BGRX1010102 = fourcc_code!('B', 'X', '3', '0');
+
+    /// [31:0] A:R:G:B 2:10:10:10 little endian
+    ARGB2101010 = fourcc_code!('A', 'R', '3', '0');
+    /// [31:0] A:B:G:R 2:10:10:10 little endian
+    ABGR2101010 = fourcc_code!('A', 'B', '3', '0');
+    /// [31:0] R:G:B:A 10:10:10:2 little endian
+    RGBA1010102 = fourcc_code!('R', 'A', '3', '0');
+    /// [31:0] B:G:R:A 10:10:10:2 little endian
+    BGRA1010102 = fourcc_code!('B', 'A', '3', '0');
+
+    // 64 bpp RGB
+
+    /// [63:0] x:R:G:B 16:16:16:16 little endian
+    XRGB16161616 = fourcc_code!('X', 'R', '4', '8');
+    /// [63:0] x:B:G:R 16:16:16:16 little endian
+    XBGR16161616 = fourcc_code!('X', 'B', '4', '8');
+
+    /// [63:0] A:R:G:B 16:16:16:16 little endian
+    ARGB16161616 = fourcc_code!('A', 'R', '4', '8');
+    /// [63:0] A:B:G:R 16:16:16:16 little endian
+    ABGR16161616 = fourcc_code!('A', 'B', '4', '8');
+
+    // Floating point 64 bpp RGB
+    // IEEE 754-2008 binary16 half-precision float
+
+    /// [63:0] x:R:G:B 16:16:16:16 little endian
+    XRGB16161616F = fourcc_code!('X', 'R', '4', 'H');
+    /// [63:0] x:B:G:R 16:16:16:16 little endian
+    XBGR16161616F = fourcc_code!('X', 'B', '4', 'H');
+
+    /// [63:0] A:R:G:B 16:16:16:16 little endian
+    ARGB16161616F = fourcc_code!('A', 'R', '4', 'H');
+    /// [63:0] A:B:G:R 16:16:16:16 little endian
+    ABGR16161616F = fourcc_code!('A', 'B', '4', 'H');
+
+    /// [63:0] A:x:B:x:G:x:R:x 10:6:10:6:10:6:10:6 little endian
+    AXBXGXRX106106106106 = fourcc_code!('A', 'B', '1', '0');
+
+    // packed YCbCr
+
+    /// [31:0] Cr0:Y1:Cb0:Y0 8:8:8:8 little endian
+    YUYV = fourcc_code!('Y', 'U', 'Y', 'V');
+    /// [31:0] Cb0:Y1:Cr0:Y0 8:8:8:8 little endian
+    YVYU = fourcc_code!('Y', 'V', 'Y', 'U');
+    /// [31:0] Y1:Cr0:Y0:Cb0 8:8:8:8 little endian
+    UYVY = fourcc_code!('U', 'Y', 'V', 'Y');
+    /// [31:0] Y1:Cb0:Y0:Cr0 8:8:8:8 little endian
+    VYUY = fourcc_code!('V', 'Y', 'U', 'Y');
+
+    /// [31:0] A:Y:Cb:Cr 8:8:8:8 little endian
+    AYUV = fourcc_code!('A', 'Y', 'U', 'V');
+    /// [31:0] Y:Cb:Cr:A 8:8:8:8 little endian
+    AVUY8888 = fourcc_code!('A', 'V', 'U', 'Y');
+    /// [31:0] X:Y:Cb:Cr 8:8:8:8 little endian
+    XYUV8888 = fourcc_code!('X', 'Y', 'U', 'V');
+    /// [31:0] Y:Cb:Cr:X 8:8:8:8 little endian
+    XVUY8888 = fourcc_code!('X', 'V', 'U', 'Y');
+    /// [23:0] Cr:Cb:Y 8:8:8 little endian
+    VUY888 = fourcc_code!('V', 'U', '2', '4');
+
+    /// [63:0] Cr0:0:Y1:0:Cb0:0:Y0:0 10:6:10:6:10:6:10:6 little endian per 2 Y pixels
+    Y210 = fourcc_code!('Y', '2', '1', '0');
+    /// [63:0] Cr0:0:Y1:0:Cb0:0:Y0:0 12:4:12:4:12:4:12:4 little endian per 2 Y pixels
+    Y212 = fourcc_code!('Y', '2', '1', '2');
+    /// [63:0] Cr0:Y1:Cb0:Y0 16:16:16:16 little endian per 2 Y pixels
+    Y216 = fourcc_code!('Y', '2', '1', '6');
+
+    /// [31:0] A:Cr:Y:Cb 2:10:10:10 little endian
+    Y410 = fourcc_code!('Y', '4', '1', '0');
+    /// [63:0] A:0:Cr:0:Y:0:Cb:0 12:4:12:4:12:4:12:4 little endian
+    Y412 = fourcc_code!('Y', '4', '1', '2');
+    /// [63:0] A:Cr:Y:Cb 16:16:16:16 little endian
+    Y416 = fourcc_code!('Y', '4', '1', '6');
+
+    /// [31:0] X:Cr:Y:Cb 2:10:10:10 little endian
+    XVYU2101010 = fourcc_code!('X', 'V', '3', '0');
+    /// [63:0] X:0:Cr:0:Y:0:Cb:0 12:4:12:4:12:4:12:4 little endian
+    XVYU12_16161616 = fourcc_code!('X', 'V', '3', '6');
+    /// [63:0] X:Cr:Y:Cb 16:16:16:16 little endian
+    XVYU16161616 = fourcc_code!('X', 'V', '4', '8');
+
+    // 2 plane RGB + A
+    // index 0 = RGB plane, same format as the corresponding non _A8 format has
+    // index 1 = A plane, [7:0] A
+
+    /// RGB plane of XRGB8888 with an A8 plane
+    XRGB8888_A8 = fourcc_code!('X', 'R', 'A', '8');
+    /// RGB plane of XBGR8888 with an A8 plane
+    XBGR8888_A8 = fourcc_code!('X', 'B', 'A', '8');
+    /// RGB plane of RGBX8888 with an A8 plane
+    RGBX8888_A8 = fourcc_code!('R', 'X', 'A', '8');
+    /// RGB plane of BGRX8888 with an A8 plane
+    BGRX8888_A8 = fourcc_code!('B', 'X', 'A', '8');
+    /// RGB plane of RGB888 with an A8 plane
+    RGB888_A8 = fourcc_code!('R', '8', 'A', '8');
+    /// RGB plane of BGR888 with an A8 plane
+    BGR888_A8 = fourcc_code!('B', '8', 'A', '8');
+    /// RGB plane of RGB565 with an A8 plane
+    RGB565_A8 = fourcc_code!('R', '5', 'A', '8');
+    /// RGB plane of BGR565 with an A8 plane
+    BGR565_A8 = fourcc_code!('B', '5', 'A', '8');
+
+    // 2 plane YCbCr
+    // index 0 = Y plane, [7:0] Y
+    // index 1 = Cr:Cb plane, [15:0] Cr:Cb little endian
+    // or
+    // index 1 = Cb:Cr plane, [15:0] Cb:Cr little endian
+
+    /// 2x2 subsampled Cr:Cb plane
+    NV12 = fourcc_code!('N', 'V', '1', '2');
+    /// 2x2 subsampled Cb:Cr plane
+    NV21 = fourcc_code!('N', 'V', '2', '1');
+    /// 2x1 subsampled Cr:Cb plane
+    NV16 = fourcc_code!('N', 'V', '1', '6');
+    /// 2x1 subsampled Cb:Cr plane
+    NV61 = fourcc_code!('N', 'V', '6', '1');
+    /// Non-subsampled Cr:Cb plane
+    NV24 = fourcc_code!('N', 'V', '2', '4');
+    /// Non-subsampled Cb:Cr plane
+    NV42 = fourcc_code!('N', 'V', '4', '2');
+
+    // 2 plane YCbCr MSB aligned
+    // index 0 = Y plane, [15:0] Y:x little endian
+    // index 1 = Cr:Cb plane, [31:0] Cr:x:Cb:x little endian
+
+    /// 2x1 subsampled Cr:Cb plane, 10 bit per channel
+    P210 = fourcc_code!('P', '2', '1', '0');
+    /// 2x2 subsampled Cr:Cb plane, 10 bit per channel
+    P010 = fourcc_code!('P', '0', '1', '0');
+    /// 2x2 subsampled Cr:Cb plane, 12 bit per channel
+    P012 = fourcc_code!('P', '0', '1', '2');
+    /// 2x2 subsampled Cr:Cb plane, 16 bit per channel
+    P016 = fourcc_code!('P', '0', '1', '6');
+
+    // 3 plane non-subsampled YCbCr, 16 bits per component
+    // index 0 = Y plane, [15:0] z:Y little endian
+    // index 1 = Cb plane, [15:0] z:Cb little endian or Cr plane, [15:0] z:Cr little endian
+    // index 2 = Cr plane, [15:0] z:Cr little endian or Cb plane, [15:0] z:Cb little endian
+
+    /// Cb plane before Cr plane, 10 bits per component
+    Q410 = fourcc_code!('Q', '4', '1', '0');
+    /// Cr plane before Cb plane, 10 bits per component
+    Q401 = fourcc_code!('Q', '4', '0', '1');
+
+    // 3 plane YCbCr
+    // index 0: Y plane, [7:0] Y
+    // index 1: Cb plane, [7:0] Cb
+    // index 2: Cr plane, [7:0] Cr
+    // or
+    // index 1: Cr plane, [7:0] Cr
+    // index 2: Cb plane, [7:0] Cb
+
+    /// 4x4 subsampled Cb (1) and Cr (2) planes
+    YUV410 = fourcc_code!('Y', 'U', 'V', '9');
+    /// 4x4 subsampled Cr (1) and Cb (2) planes
+    YVU410 = fourcc_code!('Y', 'V', 'U', '9');
+    /// 4x1 subsampled Cb (1) and Cr (2) planes
+    YUV411 = fourcc_code!('Y', 'U', '1', '1');
+    /// 4x1 subsampled Cr (1) and Cb (2) planes
+    YVU411 = fourcc_code!('Y', 'V', '1', '1');
+    /// 2x2 subsampled Cb (1) and Cr (2) planes
+    YUV420 = fourcc_code!('Y', 'U', '1', '2');
+    /// 2x2 subsampled Cr (1) and Cb (2) planes
+    YVU420 = fourcc_code!('Y', 'V', '1', '2');
+    /// 2x1 subsampled Cb (1) and Cr (2) planes
+    YUV422 = fourcc_code!('Y', 'U', '1', '6');
+    /// 2x1 subsampled Cr (1) and Cb (2) planes
+    YVU422 = fourcc_code!('Y', 'V', '1', '6');
+    /// Non-subsampled Cb (1) and Cr (2) planes
+    YUV444 = fourcc_code!('Y', 'U', '2', '4');
+    /// Non-subsampled Cr (1) and Cb (2) planes
+    YVU444 = fourcc_code!('Y', 'V', '2', '4');
+}
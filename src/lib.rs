@@ -21,65 +21,313 @@
 //! An interface to libgbm, a generic buffer manager for Linux which is provided by Mesa.
 //!
 //! Libgbm can be used to retrieve framebuffers from GPUs in a driver-independant manner.
+//!
+//! With the ```dlopen``` feature libgbm.so.1 is loaded at runtime rather than linked, so
+//! programs still start on machines without it. See ```load()```.
+//!
+//! The ```software``` feature adds ```Device::software()```, a stand-in for libgbm allocating
+//! buffers from memfds, for testing without a GPU. The ```mock``` feature builds on it with
+//! scripted failures, see the ```mock``` module.
+//!
+//! The ```egl``` feature adds the ```egl``` module, for rendering to Surfaces with EGL.
 
 #![crate_name = "gbm_rs"]
 #![crate_type = "lib"]
 
+#[macro_use]
+extern crate bitflags;
 extern crate libc;
 
+#[cfg(feature = "egl")]
+pub mod egl;
+mod error;
+mod ffi;
+pub mod format;
+pub mod modifier;
+pub mod transfer;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "software")]
+mod software;
+
+pub use error::{Error, Operation, Result};
+pub use ffi::{load, Capabilities};
+pub use format::{Format, Pixel};
+pub use modifier::Modifier;
+pub use transfer::Transfer;
+use transfer::{ReadWrite, Readable, Writable};
+use error::clear_errno;
+use ffi::Api;
+
+use std::any::Any;
+use std::marker::PhantomData;
+#[cfg(feature = "egl")]
+use std::cell::Cell;
+use std::env;
+use std::ffi::CStr;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::fs::OpenOptions;
 use std::os::unix::prelude::*;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::Mutex;
 use libc::{
     c_int,
+    c_uint,
     c_void,
-    uint32_t,
-    uint64_t,
-    size_t,
 };
 
+/// Held while ```GBM_BACKEND``` is changed by ```Device::with_backend()```,
+/// and while other Devices are created so they don't see the change
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
 /// Analogous to gbm_device.
 ///
 /// Used to perform memory allocations for a DRM device.
+/// The Device owns the file descriptor it was created with and closes it
+/// when dropped.
 pub struct Device {
-    ptr: *const gbm_device
+    ptr: *const gbm_device,
+    fd: OwnedFd,
+    api: &'static Api,
+    // EGL has a single display for each Device, see egl::Display
+    #[cfg(feature = "egl")]
+    egl_display: Cell<bool>,
 }
 
 impl Device {
-    /// Creates a Device for allocating Buffers
+    /// Opens a DRM device node and creates a Device for it
+    ///
+    /// Both primary nodes such as ```/dev/dri/card0``` and render nodes such
+    /// as ```/dev/dri/renderD128``` can be used. The node is opened for reading
+    /// and writing and is not inherited by child processes.
+    ///
+    /// # Arguments
+    ///
+    /// path: The path of the device node
+    ///
+    /// # Returns
+    ///
+    /// The newly created struct gbm_device.
+    /// If opening the node or creating the device failed an ```Error``` will be returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate gbm_rs as gbm;
+    ///
+    /// let device = gbm::Device::open("/dev/dri/card0").unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Device> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(path)
+            .map_err(|err| Error::from_io(Operation::OpenDevice, &err))?;
+
+        Device::new(file)
+    }
+
+    /// Creates a Device for allocating Buffers, taking ownership of the file descriptor
     ///
     /// The file descriptor passed in is used by the backend to communicate with
     /// platform for allocating the memory. For allocations using DRI this would be
     /// the file descriptor returned when opening a device such as ```/dev/dri/card0```
     ///
+    /// The file descriptor is kept open for as long as the Device is alive and
+    /// is closed when the Device is dropped.
+    ///
     /// # Arguments
     ///
-    /// fd: The file descriptor for a backend specific device
+    /// fd: The file descriptor for a backend specific device, such as a ```File```
+    /// or an ```OwnedFd```
     ///
     /// # Returns
     ///
     /// The newly created struct gbm_device.
-    /// If the creation of the device failed None will be returned.
+    /// If the creation of the device failed an ```Error``` will be returned.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// extern crate gbm_rs as gbm;
     ///
     /// use std::fs::OpenOptions;
-    /// use std::os::unix::prelude::*;
     ///
     /// let file = OpenOptions::new().read(true).write(true).open("/dev/dri/card0").unwrap();
     ///
-    /// let device = gbm::Device::from_fd(file.as_raw_fd()).unwrap();
+    /// let device = gbm::Device::new(file).unwrap();
+    /// ```
+    pub fn new<F: Into<OwnedFd>>(fd: F) -> Result<Device> {
+        let api = ffi::api()?;
+
+        // Don't pick up a backend forced by with_backend() on another thread
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        Device::create(fd.into(), api)
+    }
+
+    /// Creates a Device backed by the software backend
+    ///
+    /// The software backend allocates linear buffers from memfds instead of
+    /// from a GPU, without going through libgbm. It supports every format
+    /// with format info, the ```Modifier::LINEAR``` modifier, mapping,
+    /// writing, and exporting and importing fds. Exported fds are memfds,
+    /// not dma-bufs, so they can't be handed to a GPU.
+    ///
+    /// Surfaces have no renderer, ```lock_front_buffer()``` hands out the first
+    /// of their four buffers that isn't locked.
+    ///
+    /// This is meant for testing code using the crate on machines without a
+    /// GPU. Enable the ```dlopen``` feature as well to not need libgbm at all.
+    ///
+    /// # Returns
+    ///
+    /// The newly created Device.
+    /// If creating the memfd backing the Device failed an ```Error``` will be returned.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate gbm_rs as gbm;
+    ///
+    /// use gbm::{BufferObject, BufferUsage, format};
+    ///
+    /// let device = gbm::Device::software().unwrap();
+    /// let buffer = BufferObject::new(&device, 64, 64, format::ARGB8888,
+    ///                                BufferUsage::CURSOR | BufferUsage::WRITE).unwrap();
+    ///
+    /// buffer.write_pixels(&[0xffffffffu32; 64 * 64]).unwrap();
+    ///
+    /// assert_eq!(device.backend_name(), "software");
+    /// assert_eq!(buffer.stride(), 256);
     /// ```
-    pub fn from_fd(fd: RawFd) -> Option<Device> {
+    #[cfg(feature = "software")]
+    pub fn software() -> Result<Device> {
+        let fd = unsafe {
+            libc::memfd_create(b"gbm-software\0".as_ptr() as *const _, libc::MFD_CLOEXEC)
+        };
+
+        if fd < 0 {
+            return Err(Error::last(Operation::OpenDevice));
+        }
+
+        Device::create(unsafe { OwnedFd::from_raw_fd(fd) }, &software::API)
+    }
+
+    fn create(fd: OwnedFd, api: &'static Api) -> Result<Device> {
         unsafe {
-            let dev = gbm_create_device(fd);
+            clear_errno();
+            let dev = (api.gbm_create_device)(fd.as_raw_fd());
 
             if dev.is_null() {
-                return None;
+                return Err(Error::last(Operation::CreateDevice));
             }
 
-            return Some(Device { ptr: dev });
+            Ok(Device {
+                ptr: dev,
+                fd,
+                api,
+                #[cfg(feature = "egl")]
+                egl_display: Cell::new(false),
+            })
+        }
+    }
+
+    /// Creates a Device using a specific libgbm backend
+    ///
+    /// Same as ```new()```, but asks libgbm to load the named backend instead
+    /// of picking one for the device, like setting the ```GBM_BACKEND```
+    /// environment variable. The backends available depend on the system,
+    /// e.g. ```"dri"``` for Mesa or ```"nvidia-drm"``` for the NVIDIA driver.
+    ///
+    /// libgbm has no way to choose the backend other than the environment, so
+    /// ```GBM_BACKEND``` is set for the whole process while the device is
+    /// created and restored afterwards. Devices created with ```new()```,
+    /// ```open()``` or ```from_fd()``` on other threads wait for it to be
+    /// restored, but nothing else does.
+    ///
+    /// If the backend can't be loaded libgbm falls back to its usual choice,
+    /// use ```backend_name()``` to find out which backend was used.
+    ///
+    /// # Arguments
+    ///
+    /// fd: The file descriptor for a backend specific device, such as a ```File```
+    /// or an ```OwnedFd```
+    ///
+    /// backend: The name of the backend to load
+    ///
+    /// # Returns
+    ///
+    /// The newly created struct gbm_device.
+    /// If the creation of the device failed an ```Error``` will be returned.
+    ///
+    /// # Safety
+    ///
+    /// This sets and restores a process-wide environment variable, which races
+    /// with any other thread calling ```getenv()```, ```setenv()``` or
+    /// ```unsetenv()```, directly or through Rust's ```std::env```, libgbm,
+    /// Mesa, libc or any other library. The lock taken by this crate doesn't
+    /// protect them.
+    ///
+    /// It is only sound to call this while the process has a single thread,
+    /// e.g. at the start of ```main()``` before any threads are spawned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let file = OpenOptions::new().read(true).write(true).open("/dev/dri/card0").unwrap();
+    ///
+    /// let device = unsafe { gbm::Device::with_backend(file, "nvidia-drm").unwrap() };
+    ///
+    /// println!("using the {} backend", device.backend_name());
+    /// ```
+    pub unsafe fn with_backend<F: Into<OwnedFd>>(fd: F, backend: &str) -> Result<Device> {
+        let api = ffi::api()?;
+
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let previous = env::var_os("GBM_BACKEND");
+
+        env::set_var("GBM_BACKEND", backend);
+        let dev = Device::create(fd.into(), api);
+
+        match previous {
+            Some(previous) => env::set_var("GBM_BACKEND", previous),
+            None => env::remove_var("GBM_BACKEND"),
         }
+
+        dev
+    }
+
+    /// Creates a Device for allocating Buffers from a borrowed file descriptor
+    ///
+    /// The file descriptor is duplicated, so the Device does not depend on the
+    /// original being kept open.
+    ///
+    /// # Arguments
+    ///
+    /// fd: The file descriptor for a backend specific device
+    ///
+    /// # Returns
+    ///
+    /// The newly created struct gbm_device.
+    /// If the creation of the device failed an ```Error``` will be returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate gbm_rs as gbm;
+    ///
+    /// use std::fs::OpenOptions;
+    ///
+    /// let file = OpenOptions::new().read(true).write(true).open("/dev/dri/card0").unwrap();
+    ///
+    /// let device = gbm::Device::from_fd(&file).unwrap();
+    /// ```
+    pub fn from_fd<F: AsFd>(fd: F) -> Result<Device> {
+        let fd = fd.as_fd()
+            .try_clone_to_owned()
+            .map_err(|err| Error::from_io(Operation::CreateDevice, &err))?;
+
+        Device::new(fd)
     }
 
     /// Test if a format is supported for a given set of usage flags
@@ -88,34 +336,154 @@ impl Device {
     ///
     /// format: The fourcc code to test
     ///
-    /// usage: A bitmask of the usages to test the format against
+    /// usage: The usages to test the format against
     ///
     /// # Returns
     ///
     /// true if the format is supported otherwise false
-    pub fn is_format_supported(&self, format: u32, usage: u32) -> bool {
-        unsafe { gbm_device_is_format_supported(self.ptr, format, usage) != 0 }
+    pub fn is_format_supported(&self, format: Format, usage: BufferUsage) -> bool {
+        unsafe { (self.api.gbm_device_is_format_supported)(self.ptr, format.0, usage.bits()) != 0 }
     }
 
-    /// Returns the file descriptor for the Device
+    /// Get the number of planes of a format with a modifier
+    ///
+    /// Without ```gbm_device_get_format_modifier_plane_count()``` the count is
+    /// only known for ```Modifier::LINEAR```, from the format info, for formats
+    /// the Device supports with any usage flag. Like libgbm, ```None``` is
+    /// returned for ```Modifier::INVALID``` either way.
+    ///
+    /// # Arguments
+    ///
+    /// format: The format to query
+    ///
+    /// modifier: The modifier to query
+    ///
+    /// # Returns
+    ///
+    /// The number of planes of buffers with that format and modifier, or
+    /// ```None``` if the Device doesn't support the combination
+    pub fn format_modifier_plane_count(&self, format: Format, modifier: Modifier) -> Option<u32> {
+        match self.api.gbm_device_get_format_modifier_plane_count {
+            Some(get_plane_count) => {
+                let count = unsafe { get_plane_count(self.ptr, format.0, modifier.0) };
+
+                if count > 0 { Some(count as u32) } else { None }
+            }
+            None if modifier == Modifier::LINEAR => {
+                let supported = BufferUsage::all().iter()
+                    .any(|flag| self.is_format_supported(format, flag));
+
+                format.info().filter(|_| supported).map(|info| info.planes)
+            }
+            None => None,
+        }
+    }
+
+    /// Build a table of the formats and modifiers supported by the Device
+    ///
+    /// Each format is tested against every flag of ```usage``` separately and
+    /// against every modifier. Formats supporting neither are left out.
+    ///
+    /// libgbm has no way to list the modifiers of a format, so the candidates
+    /// have to be given, e.g. from the ```IN_FORMATS``` property of a KMS
+    /// plane or from ```modifier::all()```.
+    ///
+    /// # Arguments
+    ///
+    /// formats: The formats to test, such as ```format::all()```
+    ///
+    /// modifiers: The modifiers to test each format with
+    ///
+    /// usage: The usage flags to test each format with
     ///
     /// # Returns
     ///
-    /// The Fd that the Device was created with
+    /// The supported usage flags and modifiers of each supported format
     ///
     /// # Example
+    /// ```ignore
+    /// let modifiers: Vec<_> = gbm::modifier::all().collect();
+    /// let table = device.format_table(gbm::format::all(), &modifiers,
+    ///                                 gbm::BufferUsage::SCANOUT |
+    ///                                 gbm::BufferUsage::RENDERING);
+    ///
+    /// for entry in &table {
+    ///     println!("{}: {:?}", entry.format, entry.usage);
+    ///
+    ///     for support in &entry.modifiers {
+    ///         println!("    {} ({} planes)", support.modifier, support.plane_count);
+    ///     }
+    /// }
     /// ```
+    pub fn format_table<I>(&self, formats: I, modifiers: &[Modifier],
+                           usage: BufferUsage) -> Vec<FormatSupport>
+        where I: IntoIterator<Item = Format>
+    {
+        formats.into_iter()
+            .filter_map(|format| {
+                let usage = usage.iter()
+                    .filter(|&flag| self.is_format_supported(format, flag))
+                    .collect::<BufferUsage>();
+
+                let modifiers = modifiers.iter()
+                    .filter_map(|&modifier| {
+                        self.format_modifier_plane_count(format, modifier)
+                            .map(|plane_count| ModifierSupport { modifier, plane_count })
+                    })
+                    .collect::<Vec<_>>();
+
+                if usage.is_empty() && modifiers.is_empty() {
+                    return None;
+                }
+
+                Some(FormatSupport { format, usage, modifiers })
+            })
+            .collect()
+    }
+
+    /// Get the name of the libgbm backend used by the Device
+    ///
+    /// # Returns
+    ///
+    /// The name reported by the backend, e.g. ```"drm"``` for Mesa
+    pub fn backend_name(&self) -> &str {
+        unsafe {
+            let name = (self.api.gbm_device_get_backend_name)(self.ptr);
+
+            if name.is_null() {
+                return "";
+            }
+
+            CStr::from_ptr(name).to_str().unwrap_or("")
+        }
+    }
+
+    /// Get the optional parts of libgbm the Device can use
+    ///
+    /// # Returns
+    ///
+    /// The functions provided by the libgbm the Device was created through
+    pub fn capabilities(&self) -> Capabilities {
+        self.api.capabilities()
+    }
+
+    /// Returns the file descriptor for the Device
+    ///
+    /// # Returns
+    ///
+    /// The Fd that the Device was created with, borrowed for the lifetime of the Device
+    ///
+    /// # Example
+    /// ```no_run
     /// # extern crate gbm_rs as gbm;
-    /// # use std::fs::OpenOptions;
     /// # use std::os::unix::prelude::*;
-    /// # let file = OpenOptions::new().read(true).write(true).open("/dev/dri/card0").unwrap();
-    /// # let device = gbm::Device::from_fd(file.as_raw_fd()).unwrap();
+    /// # let device = gbm::Device::open("/dev/dri/card0").unwrap();
     /// let fd = device.fd();
     ///
-    /// assert_eq!(fd, file.as_raw_fd());
+    /// assert!(fd.as_raw_fd() >= 0);
     /// ```
-    pub fn fd(&self) -> RawFd {
-        unsafe { gbm_device_get_fd(self.ptr) }
+    pub fn fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
     }
 
     /// Returns the gbm_device for the Device
@@ -130,23 +498,45 @@ impl Device {
 
 impl Drop for Device {
     fn drop(&mut self) {
-        unsafe { gbm_device_destroy(self.ptr) }
+        unsafe { (self.api.gbm_device_destroy)(self.ptr) }
     }
 }
 
+/// The support for a format reported by ```Device::format_table()```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSupport {
+    /// The format
+    pub format: Format,
+    /// The usage flags the format is supported with
+    pub usage: BufferUsage,
+    /// The modifiers the format is supported with
+    pub modifiers: Vec<ModifierSupport>,
+}
+
+/// A modifier supported with a format, part of a ```FormatSupport```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModifierSupport {
+    /// The modifier
+    pub modifier: Modifier,
+    /// The number of planes of buffers with the format and modifier
+    pub plane_count: u32,
+}
+
 /// Analogous to gbm_surface
 ///
 /// Represents an area where a buffer object will be displayed.
-pub struct Surface {
-    ptr: *const gbm_surface
+/// A Surface borrows the Device it was created from and cannot outlive it.
+pub struct Surface<'dev> {
+    ptr: *const gbm_surface,
+    dev: &'dev Device,
 }
 
-impl Surface {
+impl<'dev> Surface<'dev> {
     /// Allocate a Surface object
     ///
     /// # Arguments
     ///
-    /// dev: The Device returned from Device::from_fd()
+    /// dev: The Device returned from Device::open()
     ///
     /// width: The width for the surface
     ///
@@ -154,35 +544,101 @@ impl Surface {
     ///
     /// format: The fourcc code for the surface
     ///
-    /// flags: A bitmask of the flags for this surface
+    /// usage: The union of the usage flags for this surface
     ///
     /// # Returns
     ///
     /// A newly allocated surface.
-    /// If an error occurs during allocation ```None``` will be returned.
+    /// If an error occurs during allocation an ```Error``` will be returned.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// # extern crate gbm_rs as gbm;
-    /// # use std::fs::OpenOptions;
-    /// # use std::os::unix::prelude::*;
-    /// # let file = OpenOptions::new().read(true).write(true).open("/dev/dri/card0").unwrap();
-    /// # let device = gbm::Device::from_fd(file.as_raw_fd()).unwrap();
+    /// # let device = gbm::Device::open("/dev/dri/card0").unwrap();
     /// let surface = gbm::Surface::new(&device, 1920, 1080,
     ///                                 gbm::format::XRGB8888, // GBM_FORMAT_XRGB8888
-    ///                                 gbm::USE_SCANOUT | gbm::USE_RENDERING).unwrap();
+    ///                                 gbm::BufferUsage::SCANOUT |
+    ///                                 gbm::BufferUsage::RENDERING).unwrap();
     /// ```
-    pub fn new(dev: &Device, width: u32, height: u32,
-                       format: u32, flags: u32) -> Option<Surface> {
+    pub fn new(dev: &'dev Device, width: u32, height: u32,
+               format: Format, usage: BufferUsage) -> Result<Surface<'dev>> {
         unsafe {
-            let surf = gbm_surface_create(dev.ptr, width, height,
-                                          format, flags);
+            clear_errno();
+            let surf = (dev.api.gbm_surface_create)(dev.ptr, width, height,
+                                                    format.0, usage.bits());
 
             if surf.is_null() {
-                return None;
+                return Err(Error::last(Operation::CreateSurface));
+            }
+
+            Ok(Surface { ptr: surf, dev })
+        }
+    }
+
+    /// Allocate a Surface object with an explicit list of format modifiers
+    ///
+    /// The backend picks one of the modifiers it considers optimal for the
+    /// given usage. The chosen modifier can be queried on the buffers returned
+    /// by ```lock_front_buffer()```.
+    ///
+    /// Older versions of libgbm without ```gbm_surface_create_with_modifiers2()```
+    /// fall back to ```gbm_surface_create_with_modifiers()```, which ignores the
+    /// usage flags, and then to ```gbm_surface_create()``` if ```Modifier::INVALID```
+    /// or ```Modifier::LINEAR``` is in the list. See ```Device::capabilities()```.
+    ///
+    /// # Arguments
+    ///
+    /// dev: The Device returned from Device::open()
+    ///
+    /// width: The width for the surface
+    ///
+    /// height: The height for the surface
+    ///
+    /// format: The fourcc code for the surface
+    ///
+    /// modifiers: The format modifiers the surface buffers may be allocated with
+    ///
+    /// usage: The union of the usage flags for this surface
+    ///
+    /// # Returns
+    ///
+    /// A newly allocated surface.
+    /// If an error occurs during allocation an ```Error``` will be returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let surface = gbm::Surface::with_modifiers(&device, 1920, 1080,
+    ///                                            gbm::format::XRGB8888,
+    ///                                            &[gbm::Modifier::LINEAR],
+    ///                                            gbm::BufferUsage::SCANOUT |
+    ///                                            gbm::BufferUsage::RENDERING).unwrap();
+    /// ```
+    pub fn with_modifiers(dev: &'dev Device, width: u32, height: u32, format: Format,
+                          modifiers: &[Modifier], usage: BufferUsage) -> Result<Surface<'dev>> {
+        let api = dev.api;
+        let ptr = modifiers.as_ptr() as *const u64;
+        let count = modifiers.len() as c_uint;
+
+        unsafe {
+            clear_errno();
+            let surf = if let Some(create) = api.gbm_surface_create_with_modifiers2 {
+                create(dev.ptr, width, height, format.0, ptr, count, usage.bits())
+            } else if let Some(create) = api.gbm_surface_create_with_modifiers {
+                create(dev.ptr, width, height, format.0, ptr, count)
+            } else if modifiers.contains(&Modifier::INVALID) {
+                (api.gbm_surface_create)(dev.ptr, width, height, format.0, usage.bits())
+            } else if modifiers.contains(&Modifier::LINEAR) {
+                (api.gbm_surface_create)(dev.ptr, width, height, format.0,
+                                         (usage | BufferUsage::LINEAR).bits())
+            } else {
+                return Err(Error::unsupported(Operation::CreateSurface));
+            };
+
+            if surf.is_null() {
+                return Err(Error::last(Operation::CreateSurface));
             }
 
-            return Some(Surface { ptr: surf });
+            Ok(Surface { ptr: surf, dev })
         }
     }
 
@@ -194,31 +650,30 @@ impl Surface {
     /// the application must check for a free buffer before rendering.
     ///
     /// If a surface doesn't have a free buffer, the application must
-    /// return a buffer to the surface using ```release_buffer()```
+    /// return a buffer to the surface by dropping or releasing a LockedBuffer
     /// and after that, the application can query for free buffers again.
     ///
     /// # Returns
     ///
     /// ```true``` if the surface has free buffers, ```false``` otherwise
     pub fn has_free_buffers(&self) -> bool {
-        unsafe { gbm_surface_has_free_buffers(self.ptr) != 0 }
+        unsafe { (self.dev.api.gbm_surface_has_free_buffers)(self.ptr) != 0 }
     }
 
-    /// Lock rendering to the surface's current front buffer until it is
-    /// released with ```release_ buffer()```
+    /// Lock rendering to the surface's current front buffer until the
+    /// returned LockedBuffer is dropped
     ///
     /// This function must be called exactly once after calling
     /// eglSwapBuffers. Calling it before any eglSwapBuffer has happend
     /// on the surface or two or more times after eglSwapBuffers is an
-    /// error. A new BufferObject representing the new front buffer is returned. On
-    /// multiple invocations, all the returned BufferObjects must be released in
+    /// error. A new LockedBuffer representing the new front buffer is returned. On
+    /// multiple invocations, all the returned LockedBuffers must be released in
     /// order to release the actual surface buffer.
     ///
     /// # Returns
     ///
-    /// A buffer object that should be released with ```release_buffer()```
-    /// when no longer needed.
-    /// If an error occurs this function returns ```None```.
+    /// A locked buffer that is released back to this Surface when dropped.
+    /// If an error occurs this function returns an ```Error```.
     ///
     /// # Example
     /// ```ignore
@@ -226,34 +681,34 @@ impl Surface {
     ///
     /// let buffer = surface.lock_front_buffer().unwrap();
     ///
-    /// // Output to the screen, etc.
+    /// // Output to the screen, and keep the buffer locked until the
+    /// // next page flip has completed
     ///
-    /// surface.release_buffer(buffer);
+    /// buffer.release();
     /// ```
-    pub fn lock_front_buffer(&self) -> Option<BufferObject> {
+    pub fn lock_front_buffer(&self) -> Result<LockedBuffer<'_>> {
         unsafe {
-            let bo = gbm_surface_lock_front_buffer(self.ptr);
+            clear_errno();
+            let bo = (self.dev.api.gbm_surface_lock_front_buffer)(self.ptr);
+
             if bo.is_null() {
-                return None;
+                return Err(Error::last(Operation::LockFrontBuffer));
             }
 
-            return Some(BufferObject { ptr: bo, manual: false });
+            Ok(LockedBuffer {
+                surface: self,
+                bo: BufferObject { ptr: bo, dev: self.dev, manual: false },
+            })
         }
     }
 
-    /// Release a locked buffer obtained ```lock_front_buffer()```
-    ///
-    /// Returns the underlying buffer to the Surface. Releasing a BufferObject
-    /// will typically ```has_free_buffer()``` return true and thus
-    /// allow rendering the next frame, but not always. The implementation
-    /// may choose to destroy the BufferObject immediately or reuse it, in which case
-    /// the user data associated with it is unchanged.
+    /// Get the Device used to create the Surface
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// bo: The BufferObject to be released
-    pub fn release_buffer(&self, bo: BufferObject) {
-        unsafe { gbm_surface_release_buffer(self.ptr, bo.ptr) }
+    /// The Device with which the Surface was created
+    pub fn device(&self) -> &'dev Device {
+        self.dev
     }
 
     /// Returns the gbm_surface for the Surface
@@ -266,26 +721,114 @@ impl Surface {
     }
 }
 
-impl Drop for Surface {
+impl<'dev> Drop for Surface<'dev> {
+    fn drop(&mut self) {
+        unsafe { (self.dev.api.gbm_surface_destroy)(self.ptr) }
+    }
+}
+
+/// A front buffer locked with ```Surface::lock_front_buffer()```
+///
+/// Dereferences to the locked BufferObject. The buffer stays locked, and so
+/// is not rendered to, for as long as the LockedBuffer is alive, which allows
+/// it to be kept across a page flip. It is released back to the Surface it
+/// was locked from when dropped.
+pub struct LockedBuffer<'s> {
+    surface: &'s Surface<'s>,
+    bo: BufferObject<'s>,
+}
+
+impl<'s> LockedBuffer<'s> {
+    /// Release the buffer back to its Surface
+    ///
+    /// Releasing a buffer will typically make ```has_free_buffers()``` return
+    /// true and thus allow rendering the next frame, but not always. The
+    /// implementation may choose to destroy the buffer immediately or reuse it,
+    /// in which case the user data associated with it is unchanged.
+    ///
+    /// This is equivalent to dropping the LockedBuffer.
+    pub fn release(self) {}
+
+    /// Get the Surface the buffer was locked from
+    ///
+    /// # Returns
+    ///
+    /// The Surface the buffer will be released to
+    pub fn surface(&self) -> &'s Surface<'s> {
+        self.surface
+    }
+
+    /// Attach a value to the locked buffer
+    ///
+    /// See ```BufferObject::set_user_data()```.
+    pub fn set_user_data<T: 'static>(&mut self, data: T) {
+        self.bo.set_user_data(data)
+    }
+
+    /// Get the value attached to the locked buffer for modification
+    ///
+    /// See ```BufferObject::user_data_mut()```.
+    pub fn user_data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.bo.user_data_mut()
+    }
+
+    /// Map a region of the locked buffer for CPU access
+    ///
+    /// See ```BufferObject::map()```.
+    pub fn map<T: Transfer>(&mut self, x: u32, y: u32, width: u32, height: u32,
+                            transfer: T) -> Result<Mapping<'_, T>> {
+        self.bo.map(x, y, width, height, transfer)
+    }
+}
+
+// Only shared access is given out, as swapping the BufferObject out of the
+// guard would release it to the wrong Surface
+impl<'s> Deref for LockedBuffer<'s> {
+    type Target = BufferObject<'s>;
+
+    fn deref(&self) -> &BufferObject<'s> {
+        &self.bo
+    }
+}
+
+impl<'s> Drop for LockedBuffer<'s> {
     fn drop(&mut self) {
-        unsafe { gbm_surface_destroy(self.ptr) }
+        let surface = self.surface;
+
+        unsafe { (surface.dev.api.gbm_surface_release_buffer)(surface.ptr, self.bo.ptr) }
     }
 }
 
 /// Analogous to gbm_bo
-pub struct BufferObject {
+///
+/// A BufferObject borrows the Device it was created from and cannot outlive it.
+/// Buffers returned by ```Surface::lock_front_buffer()``` additionally cannot
+/// outlive their Surface.
+///
+/// ```compile_fail
+/// # extern crate gbm_rs as gbm;
+/// let device = gbm::Device::open("/dev/dri/card0").unwrap();
+/// let buffer = gbm::BufferObject::new(&device, 64, 64, gbm::format::ARGB8888,
+///                                     gbm::BufferUsage::CURSOR |
+///                                     gbm::BufferUsage::WRITE).unwrap();
+///
+/// drop(device);
+/// buffer.width();
+/// ```
+pub struct BufferObject<'dev> {
     ptr: *const gbm_bo,
+    dev: &'dev Device,
     // To make sure we only free gbm_bo's from gbm_bo_create()
     // and NOT gbm_surface_lock_front_buffer()
     manual: bool,
 }
 
-impl BufferObject {
+impl<'dev> BufferObject<'dev> {
     /// Allocate a buffer object for the given dimensions
     ///
     /// # Arguments
     ///
-    /// dev: The Device returned from Device::from_fd()
+    /// dev: The Device returned from Device::open()
     ///
     /// width: The width for the buffer
     ///
@@ -297,90 +840,268 @@ impl BufferObject {
     ///
     /// # Returns
     ///
-    /// A newly allocated buffer. If an error occurs during allocation ```None``` will be
-    /// returned and errno set.
+    /// A newly allocated buffer. If an error occurs during allocation an ```Error```
+    /// carrying errno will be returned.
     ///
     /// # Example
     /// ```ignore
     /// let buffer = BufferObject::new(&device, 1920, 1080,
     ///                                gbm::format::XRGB8888, // GBM_FORMAT_XRGB8888
-    ///                                gbm::USE_SCANOUT | gbm::USE_RENDERING);
+    ///                                gbm::BufferUsage::SCANOUT |
+    ///                                gbm::BufferUsage::RENDERING);
     /// ```
     ///                                
-    pub fn new(dev: &Device, width: u32, height: u32,
-               format: u32, flags: u32) -> Option<BufferObject> {
+    pub fn new(dev: &'dev Device, width: u32, height: u32,
+               format: Format, usage: BufferUsage) -> Result<BufferObject<'dev>> {
         unsafe {
-            let bo = gbm_bo_create(dev.ptr, width, height,
-                                   format, flags);
+            clear_errno();
+            let bo = (dev.api.gbm_bo_create)(dev.ptr, width, height,
+                                             format.0, usage.bits());
 
             if bo.is_null() {
-                return None;
+                return Err(Error::last(Operation::CreateBufferObject));
             }
 
-            return Some(BufferObject { ptr: bo, manual: true });
+            Ok(BufferObject { ptr: bo, dev, manual: true })
         }
     }
 
-    /// Get the width of the BufferObject
-    ///
-    /// # Returns
+    /// Allocate a buffer object with an explicit list of format modifiers
     ///
-    /// The width of the allocated BufferObject
-    pub fn width(&self) -> u32 {
-        unsafe { gbm_bo_get_width(self.ptr) }
-    }
-
-    /// Get the height of the BufferObject
+    /// The backend picks one of the modifiers it considers optimal for the
+    /// given usage. Use ```modifier()``` to find out which one was chosen.
     ///
-    /// # Returns
+    /// Older versions of libgbm without ```gbm_bo_create_with_modifiers2()```
+    /// fall back to ```gbm_bo_create_with_modifiers()```, which ignores the
+    /// usage flags, and then to ```gbm_bo_create()``` if ```Modifier::INVALID```
+    /// or ```Modifier::LINEAR``` is in the list. See ```Device::capabilities()```.
     ///
-    /// The height of the allocated BufferObject
-    pub fn height(&self) -> u32 {
-        unsafe { gbm_bo_get_height(self.ptr) }
-    }
-
-    /// Get the stride of the BufferObject
+    /// # Arguments
     ///
-    /// This is calculated by the backend when it does the allocation in
-    /// BufferObject::new()
+    /// dev: The Device returned from Device::open()
     ///
-    /// # Returns
+    /// width: The width for the buffer
     ///
-    /// The stride of the allocated BufferObject in bytes
-    pub fn stride(&self) -> u32 {
-        unsafe { gbm_bo_get_stride(self.ptr) }
-    }
-
-    /// Get the format of the buffer object
+    /// height: The height for the buffer
     ///
-    /// The format of the pixels in the buffer.
+    /// format: The fourcc code for the buffer
     ///
-    /// # Returns
+    /// modifiers: The format modifiers the buffer may be allocated with
     ///
-    /// The format of buffer object, as a fourcc code
-    pub fn format(&self) -> u32 {
-        unsafe { gbm_bo_get_format(self.ptr) }
-    }
-
-    /// Get the gbm device used to create the buffer object
+    /// usage: The union of the usage flags for this buffer
     ///
     /// # Returns
     ///
-    /// Returns the gbm device with which the buffer object was created
-    pub fn device(&self) -> Device {
-        unsafe { Device { ptr: gbm_bo_get_device(self.ptr) } }
-    }
-
-    /// Get the handle of the buffer object
-    ///
-    /// This is stored in the platform generic union gbm_bo_handle type. However
-    /// the format of this handle is platform specific.
+    /// A newly allocated buffer. If an error occurs during allocation an ```Error```
+    /// carrying errno will be returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let buffer = BufferObject::with_modifiers(&device, 1920, 1080,
+    ///                                           gbm::format::XRGB8888,
+    ///                                           &[gbm::Modifier::LINEAR],
+    ///                                           gbm::BufferUsage::SCANOUT |
+    ///                                           gbm::BufferUsage::RENDERING);
+    /// ```
+    pub fn with_modifiers(dev: &'dev Device, width: u32, height: u32, format: Format,
+                          modifiers: &[Modifier], usage: BufferUsage) -> Result<BufferObject<'dev>> {
+        let api = dev.api;
+        let ptr = modifiers.as_ptr() as *const u64;
+        let count = modifiers.len() as c_uint;
+
+        unsafe {
+            clear_errno();
+            let bo = if let Some(create) = api.gbm_bo_create_with_modifiers2 {
+                create(dev.ptr, width, height, format.0, ptr, count, usage.bits())
+            } else if let Some(create) = api.gbm_bo_create_with_modifiers {
+                create(dev.ptr, width, height, format.0, ptr, count)
+            } else if modifiers.contains(&Modifier::INVALID) {
+                (api.gbm_bo_create)(dev.ptr, width, height, format.0, usage.bits())
+            } else if modifiers.contains(&Modifier::LINEAR) {
+                (api.gbm_bo_create)(dev.ptr, width, height, format.0,
+                                    (usage | BufferUsage::LINEAR).bits())
+            } else {
+                return Err(Error::unsupported(Operation::CreateBufferObject));
+            };
+
+            if bo.is_null() {
+                return Err(Error::last(Operation::CreateBufferObject));
+            }
+
+            Ok(BufferObject { ptr: bo, dev, manual: true })
+        }
+    }
+
+    /// Import a foreign buffer as a BufferObject
+    ///
+    /// The buffer can be a wl_buffer from a Wayland client, an EGLImage
+    /// created on the same device, or one or more dma-buf file descriptors.
+    /// The returned BufferObject is owned by the caller and is destroyed when
+    /// dropped. File descriptors are not consumed by the import and remain
+    /// owned by the caller.
+    ///
+    /// # Arguments
+    ///
+    /// dev: The Device returned from Device::open()
+    ///
+    /// source: The buffer to import
+    ///
+    /// usage: The union of the usage flags for this buffer
+    ///
+    /// # Returns
+    ///
+    /// The imported buffer. If an error occurs during import an ```Error``` carrying
+    /// errno will be returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let buffer = BufferObject::import(&device,
+    ///                                   gbm::ImportSource::Fd {
+    ///                                       fd: dmabuf.as_fd(),
+    ///                                       width: 1920,
+    ///                                       height: 1080,
+    ///                                       stride: 7680,
+    ///                                       format: gbm::format::XRGB8888,
+    ///                                   },
+    ///                                   gbm::BufferUsage::SCANOUT);
+    /// ```
+    pub fn import(dev: &'dev Device, source: ImportSource,
+                  usage: BufferUsage) -> Result<BufferObject<'dev>> {
+        let usage = usage.bits();
+        clear_errno();
+
+        // Callers vouched for the pointers when building the ImportSource
+        let bo = unsafe {
+            match source {
+                ImportSource::WlBuffer(ForeignBuffer { ptr: buffer, .. }) => {
+                    (dev.api.gbm_bo_import)(dev.ptr, GBM_BO_IMPORT_WL_BUFFER, buffer, usage)
+                }
+                ImportSource::EglImage(ForeignBuffer { ptr: image, .. }) => {
+                    (dev.api.gbm_bo_import)(dev.ptr, GBM_BO_IMPORT_EGL_IMAGE, image, usage)
+                }
+                ImportSource::Fd { fd, width, height, stride, format } => {
+                    let data = gbm_import_fd_data {
+                        fd: fd.as_raw_fd(),
+                        width,
+                        height,
+                        stride,
+                        format: format.0,
+                    };
+
+                    (dev.api.gbm_bo_import)(dev.ptr, GBM_BO_IMPORT_FD,
+                                            &data as *const _ as *const c_void, usage)
+                }
+                ImportSource::FdModifier { width, height, format, planes, modifier } => {
+                    if planes.is_empty() || planes.len() > GBM_MAX_PLANES {
+                        return Err(Error::InvalidPlaneCount(planes.len()));
+                    }
+
+                    let mut data = gbm_import_fd_modifier_data {
+                        width,
+                        height,
+                        format: format.0,
+                        num_fds: planes.len() as u32,
+                        fds: [-1; GBM_MAX_PLANES],
+                        strides: [0; GBM_MAX_PLANES],
+                        offsets: [0; GBM_MAX_PLANES],
+                        modifier: modifier.0,
+                    };
+
+                    for (i, plane) in planes.iter().enumerate() {
+                        data.fds[i] = plane.fd.as_raw_fd();
+                        data.strides[i] = plane.stride as c_int;
+                        data.offsets[i] = plane.offset as c_int;
+                    }
+
+                    (dev.api.gbm_bo_import)(dev.ptr, GBM_BO_IMPORT_FD_MODIFIER,
+                                            &data as *const _ as *const c_void, usage)
+                }
+            }
+        };
+
+        if bo.is_null() {
+            return Err(Error::last(Operation::ImportBufferObject));
+        }
+
+        Ok(BufferObject { ptr: bo, dev, manual: true })
+    }
+
+    /// Get the width of the BufferObject
+    ///
+    /// # Returns
+    ///
+    /// The width of the allocated BufferObject
+    pub fn width(&self) -> u32 {
+        unsafe { (self.dev.api.gbm_bo_get_width)(self.ptr) }
+    }
+
+    /// Get the height of the BufferObject
+    ///
+    /// # Returns
+    ///
+    /// The height of the allocated BufferObject
+    pub fn height(&self) -> u32 {
+        unsafe { (self.dev.api.gbm_bo_get_height)(self.ptr) }
+    }
+
+    /// Get the stride of the BufferObject
+    ///
+    /// This is calculated by the backend when it does the allocation in
+    /// BufferObject::new()
+    ///
+    /// # Returns
+    ///
+    /// The stride of the allocated BufferObject in bytes
+    pub fn stride(&self) -> u32 {
+        unsafe { (self.dev.api.gbm_bo_get_stride)(self.ptr) }
+    }
+
+    /// Get the format of the buffer object
+    ///
+    /// The format of the pixels in the buffer.
+    ///
+    /// # Returns
+    ///
+    /// The format of buffer object
+    pub fn format(&self) -> Format {
+        unsafe { Format((self.dev.api.gbm_bo_get_format)(self.ptr)) }
+    }
+
+    /// Get the format modifier of the buffer object
+    ///
+    /// The modifier describes the memory layout of the buffer, such as its
+    /// tiling and compression.
+    ///
+    /// # Returns
+    ///
+    /// The format modifier of the buffer object. Buffers allocated without an
+    /// explicit modifier may return ```Modifier::INVALID```.
+    pub fn modifier(&self) -> Modifier {
+        match self.dev.api.gbm_bo_get_modifier {
+            Some(get_modifier) => unsafe { Modifier(get_modifier(self.ptr)) },
+            None => Modifier::INVALID,
+        }
+    }
+
+    /// Get the gbm device used to create the buffer object
+    ///
+    /// # Returns
+    ///
+    /// Returns the gbm device with which the buffer object was created
+    pub fn device(&self) -> &'dev Device {
+        self.dev
+    }
+
+    /// Get the handle of the buffer object
+    ///
+    /// This is stored in the platform generic union gbm_bo_handle type. However
+    /// the format of this handle is platform specific.
     ///
     /// # Returns
     ///
     /// Returns the handle of the allocated BufferObject as a ```u32```
     pub fn handle_u32(&self) -> u32 {
-        unsafe { gbm_bo_get_handle(self.ptr) as u32 }
+        unsafe { (self.dev.api.gbm_bo_get_handle)(self.ptr) as u32 }
     }
 
     /// Get the handle of the buffer object
@@ -392,7 +1113,7 @@ impl BufferObject {
     ///
     /// Returns the handle of the allocated BufferObject as a ```u64```
     pub fn handle_u64(&self) -> u64 {
-        unsafe { gbm_bo_get_handle(self.ptr) }
+        unsafe { (self.dev.api.gbm_bo_get_handle)(self.ptr) }
     }
 
     /// Get the handle of the buffer object
@@ -404,7 +1125,7 @@ impl BufferObject {
     ///
     /// Returns the handle of the allocated BufferObject as a ```i32```
     pub fn handle_i32(&self) -> i32 {
-        unsafe { gbm_bo_get_handle(self.ptr) as i32 }
+        unsafe { (self.dev.api.gbm_bo_get_handle)(self.ptr) as i32 }
     }
 
     /// Get the handle of the buffer object
@@ -416,7 +1137,7 @@ impl BufferObject {
     ///
     /// Returns the handle of the allocated BufferObject as a ```i64```
     pub fn handle_i64(&self) -> i64 {
-        unsafe { gbm_bo_get_handle(self.ptr) as i64 }
+        unsafe { (self.dev.api.gbm_bo_get_handle)(self.ptr) as i64 }
     }
 
     /// Get the handle of the buffer object
@@ -428,42 +1149,364 @@ impl BufferObject {
     ///
     /// Returns the handle of the allocated BufferObject as a ```void *```
     pub fn handle_ptr(&self) -> *const c_void {
-        unsafe { gbm_bo_get_handle(self.ptr) as *const c_void }
+        unsafe { (self.dev.api.gbm_bo_get_handle)(self.ptr) as *const c_void }
     }
 
     /// Get a DMA-BUF file descriptor for the buffer object
     ///
     /// This function creates a DMA-BUF (also known as PRIME) file descriptor
-    /// handle for the buffer object. Each call to gbm_bo_get_fd() returns a new
-    /// file descriptor and the caller is responsible for closing the file
-    /// descriptor.
+    /// handle for the buffer object. Each call returns a new file descriptor,
+    /// which is closed when the returned OwnedFd is dropped.
+    ///
+    /// # Returns
+    ///
+    /// Returns a file descriptor referring to the underlying buffer.
+    /// If an error occurs an ```Error``` carrying errno will be returned.
+    pub fn fd(&self) -> Result<OwnedFd> {
+        unsafe {
+            clear_errno();
+            let fd = (self.dev.api.gbm_bo_get_fd)(self.ptr);
+
+            if fd < 0 {
+                return Err(Error::last(Operation::ExportFd));
+            }
+
+            Ok(OwnedFd::from_raw_fd(fd))
+        }
+    }
+
+    /// Export the buffer object as a set of DMA-BUF file descriptors
+    ///
+    /// Bundles a file descriptor, offset and stride for every plane together
+    /// with the dimensions, format and modifier of the buffer, which is
+    /// everything needed to import the buffer into another API.
+    ///
+    /// # Returns
+    ///
+    /// The exported buffer. If an error occurs an ```Error``` carrying errno
+    /// will be returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let export = buffer.export().unwrap();
+    ///
+    /// for plane in &export.planes {
+    ///     println!("fd {} offset {} stride {}",
+    ///              plane.fd.as_raw_fd(), plane.offset, plane.stride);
+    /// }
+    /// ```
+    pub fn export(&self) -> Result<DmaBufExport> {
+        let planes = self.planes()
+            .map(|plane| {
+                Ok(DmaBufPlane {
+                    fd: plane.fd()?,
+                    offset: plane.offset(),
+                    stride: plane.stride(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DmaBufExport {
+            width: self.width(),
+            height: self.height(),
+            format: self.format(),
+            modifier: self.modifier(),
+            planes,
+        })
+    }
+
+    /// Get the number of planes of the buffer object
+    ///
+    /// Multi-planar formats such as ```format::NV12``` store their components
+    /// in separate planes, each with its own offset and stride.
+    ///
+    /// # Returns
+    ///
+    /// The number of planes of the buffer object
+    pub fn plane_count(&self) -> u32 {
+        match self.dev.api.gbm_bo_get_plane_count {
+            Some(get_plane_count) => unsafe { get_plane_count(self.ptr) as u32 },
+            None => 1,
+        }
+    }
+
+    /// Iterate over the planes of the buffer object
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding a ```Plane``` for each plane of the buffer object
+    ///
+    /// # Example
+    /// ```ignore
+    /// for plane in buffer.planes() {
+    ///     println!("plane {}: offset {} stride {}",
+    ///              plane.index(), plane.offset(), plane.stride());
+    /// }
+    /// ```
+    pub fn planes(&self) -> Planes<'_> {
+        Planes { bo: self, index: 0, count: self.plane_count() }
+    }
+
+    /// Map a region of the buffer object for CPU access
+    ///
+    /// The region is copied back to the buffer, if required, when the returned
+    /// Mapping is dropped. The mapping covers ```height``` rows of ```stride()```
+    /// bytes each, starting at the pixel at ```(x, y)```.
+    ///
+    /// # Arguments
+    ///
+    /// x: The X coordinate of the top left corner of the region
+    ///
+    /// y: The Y coordinate of the top left corner of the region
+    ///
+    /// width: The width of the region
+    ///
+    /// height: The height of the region
+    ///
+    /// transfer: Whether the mapping is read from, written to, or both, one
+    /// of ```transfer::Read```, ```transfer::Write``` and ```transfer::ReadWrite```
     ///
     /// # Returns
     ///
-    /// Returns a file descriptor referring to the underlying buffer
-    pub fn fd(&self) -> RawFd {
-        unsafe { gbm_bo_get_fd(self.ptr) }
+    /// A guard giving access to the mapped region. If an error occurs an
+    /// ```Error``` carrying errno will be returned.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mapping = buffer.map(0, 0, buffer.width(), buffer.height(),
+    ///                          gbm::transfer::Read).unwrap();
+    ///
+    /// for row in mapping.chunks(mapping.stride() as usize) {
+    ///     // Read back the pixels of a row
+    /// }
+    /// ```
+    pub fn map<T: Transfer>(&mut self, x: u32, y: u32, width: u32, height: u32,
+                            _transfer: T) -> Result<Mapping<'_, T>> {
+        let api = self.dev.api;
+        let (map, get_bpp) = match (api.gbm_bo_map, api.gbm_bo_unmap, api.gbm_bo_get_bpp) {
+            (Some(map), Some(_), Some(get_bpp)) => (map, get_bpp),
+            _ => return Err(Error::unsupported(Operation::Map)),
+        };
+
+        unsafe {
+            let mut stride = 0;
+            let mut map_data = ptr::null_mut();
+
+            clear_errno();
+            let addr = map(self.ptr, x, y, width, height, T::FLAGS,
+                           &mut stride, &mut map_data);
+
+            if addr.is_null() {
+                return Err(Error::last(Operation::Map));
+            }
+
+            let cpp = (get_bpp(self.ptr) as usize).div_ceil(8);
+            let len = match height {
+                0 => 0,
+                _ => stride as usize * (height as usize - 1) + width as usize * cpp,
+            };
+
+            Ok(Mapping {
+                bo: self,
+                addr: addr as *mut u8,
+                len,
+                stride,
+                map_data,
+                _transfer: PhantomData,
+            })
+        }
     }
 
     /// Write data into the buffer object
     ///
-    /// If the buffer object was created with the USE_WRITE flag
+    /// If the buffer object was created with the ```BufferUsage::WRITE``` flag
     /// this function can used to write data into the buffer object. The
     /// data is copied directly into the object and it's the responsiblity
     /// of the caller to make sure the data represents valid pixel data,
     /// according to the width, height, stride and format of the buffer object.
     ///
+    /// The data may be shorter than the buffer, in which case only the start
+    /// of the buffer is written. Data longer than the planes of the buffer, as
+    /// given by their offsets, strides and heights, is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// data: The bytes to write
+    ///
+    /// # Returns
+    ///
+    /// Returns ```Ok``` on success, ```Error::InvalidWriteSize``` if the data
+    /// doesn't fit in the buffer, otherwise an ```Error``` carrying errno is
+    /// returned
+    pub fn write(&self, data: &[u8]) -> Result<()> {
+        let size = self.size()
+            .ok_or(Error::Failed { op: Operation::Write, errno: Some(libc::EOVERFLOW) })?;
+
+        if data.len() > size {
+            return Err(Error::InvalidWriteSize { len: data.len(), size });
+        }
+
+        unsafe {
+            clear_errno();
+
+            if (self.dev.api.gbm_bo_write)(self.ptr, data.as_ptr() as *const c_void, data.len()) != 0 {
+                return Err(Error::last(Operation::Write));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Write pixels into the buffer object
+    ///
+    /// Same as ```write()```, taking the data as a slice of pixels instead of
+    /// bytes.
+    ///
+    /// # Arguments
+    ///
+    /// pixels: The pixels to write
+    ///
+    /// # Returns
+    ///
+    /// Returns ```Ok``` on success, ```Error::InvalidWriteSize``` if the data
+    /// doesn't fit in the buffer, otherwise an ```Error``` carrying errno is
+    /// returned
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate gbm_rs as gbm;
+    ///
+    /// use gbm::{BufferObject, BufferUsage, format};
+    ///
+    /// let device = gbm::Device::open("/dev/dri/card0").unwrap();
+    /// let cursor = BufferObject::new(&device, 64, 64, format::ARGB8888,
+    ///                                BufferUsage::CURSOR | BufferUsage::WRITE).unwrap();
+    ///
+    /// // An opaque white cursor
+    /// cursor.write_pixels(&[0xffffffffu32; 64 * 64]).unwrap();
+    /// ```
+    pub fn write_pixels<P: Pixel>(&self, pixels: &[P]) -> Result<()> {
+        let data = unsafe {
+            slice::from_raw_parts(pixels.as_ptr() as *const u8, mem::size_of_val(pixels))
+        };
+
+        self.write(data)
+    }
+
+    /// The number of bytes spanned by the planes of the buffer object
+    ///
+    /// This is the end of the plane reaching furthest into the buffer, or
+    /// ```None``` if it can't be represented.
+    fn size(&self) -> Option<usize> {
+        let height = self.height();
+        let info = match self.format().info() {
+            Some(info) => info,
+            None => return (self.stride() as usize).checked_mul(height as usize),
+        };
+
+        // Without the plane entry points only the first plane is reported,
+        // the others are assumed to follow it with strides scaled like it
+        if self.plane_count() < info.planes {
+            let width = self.width();
+            let first = info.min_stride(0, width)?.max(1) as usize;
+            let mut end = 0usize;
+
+            for plane in 0..info.planes as usize {
+                let stride = (self.stride() as usize)
+                    .checked_mul(info.min_stride(plane, width)? as usize)? / first;
+
+                let rows = info.plane_height(plane, height) as usize;
+
+                end = end.checked_add(stride.checked_mul(rows)?)?;
+            }
+
+            return Some(end);
+        }
+
+        self.planes().try_fold(0usize, |size, plane| {
+            let rows = info.plane_height(plane.index() as usize, height);
+            let end = (plane.stride() as usize)
+                .checked_mul(rows as usize)?
+                .checked_add(plane.offset() as usize)?;
+
+            Some(size.max(end))
+        })
+    }
+
+    /// Attach a value to the buffer object
+    ///
+    /// The value is stored in the gbm_bo itself, so it survives the
+    /// BufferObject being released back to its Surface and is returned again
+    /// when libgbm hands out the same buffer from ```lock_front_buffer()```.
+    /// The value is dropped when libgbm destroys the buffer, or when it is
+    /// replaced by another call to this function.
+    ///
+    /// This assumes nothing else uses the user data slot of the gbm_bo.
+    ///
     /// # Arguments
     ///
-    /// buf: The data to write
+    /// data: The value to attach
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut buffer = surface.lock_front_buffer().unwrap();
+    ///
+    /// if buffer.user_data::<u32>().is_none() {
+    ///     let fb_id = add_framebuffer(&buffer);
+    ///     buffer.set_user_data(fb_id);
+    /// }
+    /// ```
+    pub fn set_user_data<T: 'static>(&mut self, data: T) {
+        let data: Box<Box<dyn Any>> = Box::new(Box::new(data));
+
+        unsafe {
+            let old = (self.dev.api.gbm_bo_get_user_data)(self.ptr);
+
+            (self.dev.api.gbm_bo_set_user_data)(self.ptr, Box::into_raw(data) as *mut c_void,
+                                 Some(destroy_user_data));
+
+            // libgbm does not run the old destroy callback when replacing the data
+            if !old.is_null() {
+                drop(Box::from_raw(old as *mut Box<dyn Any>));
+            }
+        }
+    }
+
+    /// Get the value attached to the buffer object
+    ///
+    /// # Returns
     ///
-    /// count: The number of bytes to write
+    /// The value previously attached with ```set_user_data()```, or ```None```
+    /// if no value is attached or it is not of type ```T```
+    pub fn user_data<T: 'static>(&self) -> Option<&T> {
+        unsafe {
+            let data = (self.dev.api.gbm_bo_get_user_data)(self.ptr) as *const Box<dyn Any>;
+
+            if data.is_null() {
+                return None;
+            }
+
+            (*data).downcast_ref()
+        }
+    }
+
+    /// Get the value attached to the buffer object for modification
     ///
     /// # Returns
     ///
-    /// Returns ```true``` on success, otherwise ```false``` is returned an errno set
-    pub fn write<T>(&self, buf: *const T, count: usize) -> bool {
-        unsafe { gbm_bo_write(self.ptr, buf as *const c_void, count) == 0 }
+    /// The value previously attached with ```set_user_data()```, or ```None```
+    /// if no value is attached or it is not of type ```T```
+    pub fn user_data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        unsafe {
+            let data = (self.dev.api.gbm_bo_get_user_data)(self.ptr) as *mut Box<dyn Any>;
+
+            if data.is_null() {
+                return None;
+            }
+
+            (*data).downcast_mut()
+        }
     }
 
     /// Returns the gbm_bo for the BufferObject
@@ -476,159 +1519,385 @@ impl BufferObject {
     }
 }
 
-impl Drop for BufferObject {
+impl<'dev> Drop for BufferObject<'dev> {
+    fn drop(&mut self) {
+        unsafe { if self.manual { (self.dev.api.gbm_bo_destroy)(self.ptr) } }
+    }
+}
+
+/// A single plane of a BufferObject
+///
+/// Returned by ```BufferObject::planes()```.
+#[derive(Clone, Copy)]
+pub struct Plane<'a> {
+    bo: &'a BufferObject<'a>,
+    index: u32,
+}
+
+impl<'a> Plane<'a> {
+    /// Get the index of the plane
+    ///
+    /// # Returns
+    ///
+    /// The index of the plane within the buffer object
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Get the offset of the plane
+    ///
+    /// # Returns
+    ///
+    /// The offset of the plane from the start of the buffer in bytes
+    pub fn offset(&self) -> u32 {
+        match self.bo.dev.api.gbm_bo_get_offset {
+            Some(get_offset) => unsafe { get_offset(self.bo.ptr, self.index as c_int) },
+            // Without plane support the buffer only has the one plane
+            None => 0,
+        }
+    }
+
+    /// Get the stride of the plane
+    ///
+    /// # Returns
+    ///
+    /// The stride of the plane in bytes
+    pub fn stride(&self) -> u32 {
+        match self.bo.dev.api.gbm_bo_get_stride_for_plane {
+            Some(get_stride) => unsafe { get_stride(self.bo.ptr, self.index as c_int) },
+            None => self.bo.stride(),
+        }
+    }
+
+    /// Get the handle of the plane
+    ///
+    /// This is stored in the platform generic union gbm_bo_handle type. However
+    /// the format of this handle is platform specific.
+    ///
+    /// # Returns
+    ///
+    /// Returns the handle of the plane as a ```u32```
+    pub fn handle_u32(&self) -> u32 {
+        self.handle_u64() as u32
+    }
+
+    /// Get the handle of the plane
+    ///
+    /// This is stored in the platform generic union gbm_bo_handle type. However
+    /// the format of this handle is platform specific.
+    ///
+    /// # Returns
+    ///
+    /// Returns the handle of the plane as a ```u64```
+    pub fn handle_u64(&self) -> u64 {
+        match self.bo.dev.api.gbm_bo_get_handle_for_plane {
+            Some(get_handle) => unsafe { get_handle(self.bo.ptr, self.index as c_int) },
+            None => self.bo.handle_u64(),
+        }
+    }
+
+    /// Get a DMA-BUF file descriptor for the plane
+    ///
+    /// Each call returns a new file descriptor, which is closed when the
+    /// returned OwnedFd is dropped.
+    ///
+    /// Older versions of libgbm without ```gbm_bo_get_fd_for_plane()``` return
+    /// the file descriptor of the whole buffer object for every plane.
+    ///
+    /// # Returns
+    ///
+    /// Returns a file descriptor referring to the underlying buffer.
+    /// If an error occurs an ```Error``` carrying errno will be returned.
+    pub fn fd(&self) -> Result<OwnedFd> {
+        let api = self.bo.dev.api;
+
+        unsafe {
+            clear_errno();
+            let fd = match api.gbm_bo_get_fd_for_plane {
+                Some(get_fd) => get_fd(self.bo.ptr, self.index as c_int),
+                // Planes of older libgbm buffers share a single allocation
+                None => (api.gbm_bo_get_fd)(self.bo.ptr),
+            };
+
+            if fd < 0 {
+                return Err(Error::last(Operation::ExportFd));
+            }
+
+            Ok(OwnedFd::from_raw_fd(fd))
+        }
+    }
+}
+
+/// A BufferObject exported as DMA-BUF file descriptors
+///
+/// Returned by ```BufferObject::export()```. The file descriptors are closed
+/// when it is dropped.
+#[derive(Debug)]
+pub struct DmaBufExport {
+    /// The width of the buffer
+    pub width: u32,
+    /// The height of the buffer
+    pub height: u32,
+    /// The format of the buffer
+    pub format: Format,
+    /// The format modifier describing the buffer layout
+    pub modifier: Modifier,
+    /// The planes making up the buffer, in order
+    pub planes: Vec<DmaBufPlane>,
+}
+
+/// A single plane of a DmaBufExport
+#[derive(Debug)]
+pub struct DmaBufPlane {
+    /// The dma-buf file descriptor containing the plane
+    pub fd: OwnedFd,
+    /// The offset of the plane from the start of the dma-buf in bytes
+    pub offset: u32,
+    /// The stride of the plane in bytes
+    pub stride: u32,
+}
+
+/// An iterator over the planes of a BufferObject
+///
+/// Returned by ```BufferObject::planes()```.
+pub struct Planes<'a> {
+    bo: &'a BufferObject<'a>,
+    index: u32,
+    count: u32,
+}
+
+impl<'a> Iterator for Planes<'a> {
+    type Item = Plane<'a>;
+
+    fn next(&mut self) -> Option<Plane<'a>> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let plane = Plane { bo: self.bo, index: self.index };
+        self.index += 1;
+
+        Some(plane)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.count - self.index) as usize;
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for Planes<'a> {}
+
+/// A region of a BufferObject mapped for CPU access
+///
+/// Returned by ```BufferObject::map()```, and unmapped when dropped. What
+/// it allows depends on the ```Transfer``` it was mapped with: ```Read```
+/// mappings dereference to the bytes of the region, ```ReadWrite``` ones
+/// mutably as well, and ```Write``` ones can be copied into.
+pub struct Mapping<'a, T: Transfer> {
+    bo: &'a BufferObject<'a>,
+    addr: *mut u8,
+    len: usize,
+    stride: u32,
+    map_data: *mut c_void,
+    _transfer: PhantomData<T>,
+}
+
+impl<'a, T: Transfer> Mapping<'a, T> {
+    /// Get the stride of the mapped region
+    ///
+    /// This may differ from the stride of the BufferObject itself.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes between the start of consecutive rows
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Get the size of the mapped region
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes from the first pixel of the region to its last
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the mapped region is empty
+    ///
+    /// # Returns
+    ///
+    /// ```true``` if the region has no pixels
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a, T: Writable> Mapping<'a, T> {
+    /// Copy data into the mapped region
+    ///
+    /// # Arguments
+    ///
+    /// offset: The offset into the region to copy to, in bytes
+    ///
+    /// data: The bytes to copy
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data doesn't fit in the region at that offset
+    pub fn copy_from_slice(&mut self, offset: usize, data: &[u8]) {
+        let end = offset.checked_add(data.len()).filter(|&end| end <= self.len);
+
+        assert!(end.is_some(), "{} bytes at offset {} overflow a mapping of {} bytes",
+                data.len(), offset, self.len);
+
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), self.addr.add(offset), data.len()) }
+    }
+}
+
+impl<'a, T: Readable> Deref for Mapping<'a, T> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.addr, self.len) }
+    }
+}
+
+impl<'a> DerefMut for Mapping<'a, ReadWrite> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.addr, self.len) }
+    }
+}
+
+impl<'a, T: Transfer> Drop for Mapping<'a, T> {
     fn drop(&mut self) {
-        unsafe { if self.manual { gbm_bo_destroy(self.ptr) } }
-    }
-}
-
-/// Buffer is going to be presented to the screen using an API such as KMS
-pub const USE_SCANOUT: u32 = (1 << 0);
-/// Buffer is going to be used as cursor
-pub const USE_CURSOR: u32 = (1 << 1);
-/// Buffer is to be used for rendering - for example it is going to be used
-/// as the storage for a color buffer
-pub const USE_RENDERING: u32 = (1 << 2);
-/// Buffer can be used for BufferObject::write. This is guaranteed to work
-/// with USE_CURSOR, but may not work for other combinations
-pub const USE_WRITE: u32 = (1 << 3);
-
-/// Formats
-pub mod format {
-    macro_rules! fourcc_code {
-        ($a:expr, $b:expr, $c:expr, $d:expr) => {
-            ($a as u32) | (($b as u32) << 8) | (($c as u32) << 16) | (($d as u32) << 24)
+        // map() only succeeds if gbm_bo_unmap is available
+        if let Some(unmap) = self.bo.dev.api.gbm_bo_unmap {
+            unsafe { unmap(self.bo.ptr, self.map_data) }
         }
     }
+}
+
+extern "C" fn destroy_user_data(_bo: *const gbm_bo, data: *mut c_void) {
+    if !data.is_null() {
+        unsafe { drop(Box::from_raw(data as *mut Box<dyn Any>)) }
+    }
+}
+
+/// A foreign buffer that can be imported with ```BufferObject::import()```
+pub enum ImportSource<'a> {
+    /// A ```struct wl_resource *``` for a wl_buffer created by a Wayland client,
+    /// built with ```ImportSource::wl_buffer()```
+    WlBuffer(ForeignBuffer<'a>),
+    /// An ```EGLImage``` created on the same device, built with
+    /// ```ImportSource::egl_image()```
+    EglImage(ForeignBuffer<'a>),
+    /// A single-plane dma-buf
+    Fd {
+        /// The dma-buf file descriptor
+        fd: BorrowedFd<'a>,
+        /// The width of the buffer
+        width: u32,
+        /// The height of the buffer
+        height: u32,
+        /// The stride of the buffer in bytes
+        stride: u32,
+        /// The format of the buffer
+        format: Format,
+    },
+    /// A dma-buf with up to four planes and an explicit format modifier
+    FdModifier {
+        /// The width of the buffer
+        width: u32,
+        /// The height of the buffer
+        height: u32,
+        /// The format of the buffer
+        format: Format,
+        /// The planes making up the buffer, in order
+        planes: &'a [ImportPlane<'a>],
+        /// The format modifier describing the buffer layout
+        modifier: Modifier,
+    },
+}
+
+impl<'a> ImportSource<'a> {
+    /// Import a wl_buffer created by a Wayland client
+    ///
+    /// # Arguments
+    ///
+    /// buffer: The ```struct wl_resource *``` of the wl_buffer
+    ///
+    /// # Safety
+    ///
+    /// ```buffer``` must refer to a wl_buffer resource that stays alive for
+    /// the lifetime ```'a``` of the ImportSource.
+    pub unsafe fn wl_buffer(buffer: *const c_void) -> ImportSource<'a> {
+        ImportSource::WlBuffer(ForeignBuffer { ptr: buffer, _resource: PhantomData })
+    }
+
+    /// Import an EGLImage created on the same device
+    ///
+    /// # Arguments
+    ///
+    /// image: The ```EGLImage```
+    ///
+    /// # Safety
+    ///
+    /// ```image``` must refer to an EGLImage that stays alive for the
+    /// lifetime ```'a``` of the ImportSource.
+    pub unsafe fn egl_image(image: *const c_void) -> ImportSource<'a> {
+        ImportSource::EglImage(ForeignBuffer { ptr: image, _resource: PhantomData })
+    }
+}
 
-    // Color index
-
-    /// [7:0] C
-    pub const C8: u32 = fourcc_code!('C', '8', ' ', ' ');
-
-    // 8 bpp RGB
-
-    /// [7:0] R:G:B 3:3:2
-    pub const RGB332: u32 = fourcc_code!('R', 'G', 'B', '8');
-    /// [7:0] B:G:R 2:3:3
-    pub const BGR233: u32 = fourcc_code!('B', 'G', 'R', '8');
-
-    // 16 bpp RGB
-
-    /// [15:0] x:R:G:B 4:4:4:4 little endian
-    pub const XRGB4444: u32 = fourcc_code!('X', 'R', '1', '2');
-    /// [15:0] x:B:G:R 4:4:4:4 little endian
-    pub const XBGR4444: u32 = fourcc_code!('X', 'B', '1', '2');
-    /// [15:0] R:G:B:x 4:4:4:4 little endian
-    pub const RGBX4444: u32 = fourcc_code!('R', 'X', '1', '2');
-    /// [15:0] B:G:R:x 4:4:4:4 little endian
-    pub const BGRX4444: u32 = fourcc_code!('B', 'X', '1', '2');
-
-    /// [15:0] A:R:G:B 4:4:4:4 little endian
-    pub const ARGB4444: u32 = fourcc_code!('A', 'R', '1', '2');
-    /// [15:0] A:B:G:R 4:4:4:4 little endian
-    pub const ABGR4444: u32 = fourcc_code!('A', 'B', '1', '2');
-    /// [15:0] R:G:B:A 4:4:4:4 little endian
-    pub const RGBA4444: u32 = fourcc_code!('R', 'A', '1', '2');
-    /// [15:0] B:G:R:A 4:4:4:4 little endian
-    pub const BGRA4444: u32 = fourcc_code!('B', 'A', '1', '2');
-
-    /// [15:0] x:R:G:B 1:5:5:5 little endian
-    pub const XRGB1555: u32 = fourcc_code!('X', 'R', '1', '5');
-    /// [15:0] x:B:G:R 1:5:5:5 little endian
-    pub const XBGR1555: u32 = fourcc_code!('X', 'B', '1', '5');
-    /// [15:0] R:G:B:x 5:5:5:1 little endian
-    pub const RGBX5551: u32 = fourcc_code!('R', 'X', '1', '5');
-    /// [15:0] B:G:R:x 5:5:5:1 little endian
-    pub const BGRX5551: u32 = fourcc_code!('B', 'X', '1', '5');
-
-    /// [15:0] A:R:G:B 1:5:5:5 little endian
-    pub const ARGB1555: u32 = fourcc_code!('A', 'R', '1', '5');
-    /// [15:0] A:B:G:R 1:5:5:5 little endian
-    pub const ABGR1555: u32 = fourcc_code!('A', 'B', '1', '5');
-    /// [15:0] R:G:B:A 5:5:5:1 little endian
-    pub const RGBA5551: u32 = fourcc_code!('R', 'A', '1', '5');
-    /// [15:0] B:G:R:A 5:5:5:1 little endian
-    pub const BGRA5551: u32 = fourcc_code!('B', 'A', '1', '5');
-
-    /// [15:0] R:G:B 5:6:5 little endian
-    pub const RGB565: u32 = fourcc_code!('R', 'G', '1', '6');
-    /// [15:0] B:G:R 5:6:5 little endian
-    pub const BGR565: u32 = fourcc_code!('B', 'G', '1', '6');
-
-    // 24 bpp RGB
-
-    /// [23:0] R:G:B little endian
-    pub const RGB888: u32 = fourcc_code!('R', 'G', '2', '4');
-    /// [23:0] B:G:R little endian
-    pub const BGR888: u32 = fourcc_code!('B', 'G', '2', '4');
-
-    // 32 bpp RGB
-
-    /// [31:0] x:R:G:B 8:8:8:8 little endian
-    pub const XRGB8888: u32 = fourcc_code!('X', 'R', '2', '4');
-    /// [31:0] x:B:G:R 8:8:8:8 little endian
-    pub const XBGR8888: u32 = fourcc_code!('X', 'B', '2', '4');
-    /// [31:0] R:G:B:x 8:8:8:8 little endian
-    pub const RGBX8888: u32 = fourcc_code!('R', 'X', '2', '4');
-    /// [31:0] B:G:R:x 8:8:8:8 little endian
-    pub const BGRX8888: u32 = fourcc_code!('B', 'X', '2', '4');
-
-    /// [31:0] A:R:G:B 8:8:8:8 little endian
-    pub const ARGB8888: u32 = fourcc_code!('A', 'R', '2', '4');
-    /// [31:0] A:B:G:R 8:8:8:8 little endian
-    pub const ABGR8888: u32 = fourcc_code!('A', 'B', '2', '4');
-    /// [31:0] R:G:B:A 8:8:8:8 little endian
-    pub const RGBA8888: u32 = fourcc_code!('R', 'A', '2', '4');
-    /// [31:0] B:G:R:A 8:8:8:8 little endian
-    pub const BGRA8888: u32 = fourcc_code!('B', 'A', '2', '4');
-
-    /// [31:0] x:R:G:B 2:10:10:10 little endian
-    pub const XRGB2101010: u32 = fourcc_code!('X', 'R', '3', '0');
-    /// [31:0] x:B:G:R 2:10:10:10 little endian
-    pub const XBGR2101010: u32 = fourcc_code!('X', 'B', '3', '0');
-    /// [31:0] R:G:B:x 10:10:10:2 little endian
-    pub const RGBX1010102: u32 = fourcc_code!('R', 'X', '3', '0');
-    /// [31:0] B:G:R:x 10:10:10:2 little endian
-    pub const BGRX1010102: u32 = fourcc_code!('B', 'X', '3', '0');
-
-    /// [31:0] A:R:G:B 2:10:10:10 little endian
-    pub const ARGB2101010: u32 = fourcc_code!('A', 'R', '3', '0');
-    /// [31:0] A:B:G:R 2:10:10:10 little endian
-    pub const ABGR2101010: u32 = fourcc_code!('A', 'B', '3', '0');
-    /// [31:0] R:G:B:A 10:10:10:2 little endian
-    pub const RGBA1010102: u32 = fourcc_code!('R', 'A', '3', '0');
-    /// [31:0] B:G:R:A 10:10:10:2 little endian
-    pub const BGRA1010102: u32 = fourcc_code!('B', 'A', '3', '0');
-
-    // packed YCbCr
-
-    /// [31:0] Cr0:Y1:Cb0:Y0 8:8:8:8 little endian
-    pub const YUYV: u32 = fourcc_code!('Y', 'U', 'Y', 'V');
-    /// [31:0] Cb0:Y1:Cr0:Y0 8:8:8:8 little endian
-    pub const YVYU: u32 = fourcc_code!('Y', 'V', 'Y', 'U');
-    /// [31:0] Y1:Cr0:Y0:Cb0 8:8:8:8 little endian
-    pub const UYVY: u32 = fourcc_code!('U', 'Y', 'V', 'Y');
-    /// [31:0] Y1:Cb0:Y0:Cr0 8:8:8:8 little endian
-    pub const VYUY: u32 = fourcc_code!('V', 'Y', 'U', 'Y');
-
-    /// [31:0] A:Y:Cb:Cr 8:8:8:8 little endian
-    pub const AYUV: u32 = fourcc_code!('A', 'Y', 'U', 'V');
-
-    // 2 plane YCbCr
-    // index 0 = Y plane, [7:0] Y
-    // index 1 = Cr:Cb plane, [15:0] Cr:Cb little endian
-    // or
-    // index 1 = Cb:Cr plane, [15:0] Cb:Cr little endian
-
-    /// 2x2 subsampled Cr:Cb plane
-    pub const NV12: u32 = fourcc_code!('N', 'V', '1', '2');
-    /// 2x2 subsampled Cb:Cr plane
-    pub const NV21: u32 = fourcc_code!('N', 'V', '2', '1');
-    /// 2x1 subsampled Cr:Cb plane
-    pub const NV16: u32 = fourcc_code!('N', 'V', '1', '6');
-    /// 2x1 subsampled Cb:Cr plane
-    pub const NV61: u32 = fourcc_code!('N', 'V', '6', '1');
+/// A pointer to a wl_buffer or EGLImage, only built by the unsafe
+/// constructors of ```ImportSource```
+///
+/// It can't be copied and is consumed by ```BufferObject::import()```, so it
+/// can't be kept around after the resource it points to is destroyed.
+#[derive(Debug)]
+pub struct ForeignBuffer<'a> {
+    ptr: *const c_void,
+    _resource: PhantomData<&'a ()>,
+}
+
+/// A single plane of a multi-plane dma-buf import
+#[derive(Clone, Copy, Debug)]
+pub struct ImportPlane<'a> {
+    /// The dma-buf file descriptor containing the plane
+    pub fd: BorrowedFd<'a>,
+    /// The stride of the plane in bytes
+    pub stride: u32,
+    /// The offset of the plane from the start of the dma-buf in bytes
+    pub offset: u32,
+}
+
+bitflags! {
+    /// How a buffer is going to be used
+    ///
+    /// Analogous to the ```GBM_BO_USE_*``` flags.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct BufferUsage: u32 {
+        /// Buffer is going to be presented to the screen using an API such as KMS
+        const SCANOUT = 1 << 0;
+        /// Buffer is going to be used as cursor
+        const CURSOR = 1 << 1;
+        /// Buffer is to be used for rendering - for example it is going to be used
+        /// as the storage for a color buffer
+        const RENDERING = 1 << 2;
+        /// Buffer can be used for BufferObject::write. This is guaranteed to work
+        /// with CURSOR, but may not work for other combinations
+        const WRITE = 1 << 3;
+        /// Buffer is linear, i.e. not tiled
+        const LINEAR = 1 << 4;
+        /// Buffer is protected, i.e. encrypted and not readable by the CPU or
+        /// any non-secure engine
+        const PROTECTED = 1 << 5;
+        /// Buffer is going to be rendered to while it is being scanned out,
+        /// so the backend should avoid layouts that need resolving
+        const FRONT_RENDERING = 1 << 6;
+    }
 }
 
 //
@@ -647,42 +1916,32 @@ pub enum gbm_bo {}
 #[allow(non_camel_case_types)]
 pub enum gbm_surface {}
 
-#[link(name = "gbm")]
-extern {
-    fn gbm_device_get_fd(gbm: *const gbm_device) -> c_int;
-    // This function doesn't seem very useful
-    // fn gbm_device_get_backend_name(gbm: *const gbm_device) -> *const c_char;
-    fn gbm_device_is_format_supported(gbm: *const gbm_device,
-                                          format: uint32_t, usage: uint32_t) -> c_int;
-    fn gbm_device_destroy(gbm: *const gbm_device);
-    fn gbm_create_device(fd: c_int) -> *const gbm_device;
-    fn gbm_bo_create(gbm: *const gbm_device,
-                         width: uint32_t, height:
-                         uint32_t, format: uint32_t, flags: uint32_t) -> *const gbm_bo;
-    // TODO
-    // fn gbm_bo_import(gbm: *const gbm_device, _type: uint32_t,
-    //                  buffer: *const c_void, usage: uint32_t) -> *const gbm_bo;
-    fn gbm_bo_get_width(bo: *const gbm_bo) -> uint32_t;
-    fn gbm_bo_get_height(bo: *const gbm_bo) -> uint32_t;
-    fn gbm_bo_get_stride(bo: *const gbm_bo) -> uint32_t;
-    fn gbm_bo_get_format(bo: *const gbm_bo) -> uint32_t;
-    fn gbm_bo_get_device(bo: *const gbm_bo) -> *const gbm_device;
-    fn gbm_bo_get_handle(bo: *const gbm_bo) -> uint64_t;
-    fn gbm_bo_get_fd(bo: *const gbm_bo) -> c_int;
-    fn gbm_bo_write(bo: *const gbm_bo, buf: *const c_void, count: size_t) -> c_int;
-    // TODO
-    // fn gbm_bo_set_user_data(bo: *const gbm_bo, data: *const c_void,
-    //                         destroy_user_data: extern fn(bo: *const gbm_bo, data: *const c_void));
-    // TODO
-    // fn gbm_bo_get_user_data(bo: *const gbm_bo) -> *const c_void;
-    fn gbm_bo_destroy(bo: *const gbm_bo);
-    fn gbm_surface_create(gbm: *const gbm_device,
-                              width: uint32_t, height: uint32_t,
-                              format: uint32_t, flags: uint32_t) -> *const gbm_surface;
-    // This function doesn't seem to have actually been implemented
-    // fn gbm_surface_needs_lock_front_buffer(surface: *const gbm_surface) -> c_int;
-    fn gbm_surface_lock_front_buffer(surface: *const gbm_surface) -> *const gbm_bo;
-    fn gbm_surface_release_buffer(surface: *const gbm_surface, bo: *const gbm_bo);
-    fn gbm_surface_has_free_buffers(surface: *const gbm_surface) -> c_int;
-    fn gbm_surface_destroy(surface: *const gbm_surface);
+const GBM_BO_IMPORT_WL_BUFFER: u32 = 0x5501;
+const GBM_BO_IMPORT_EGL_IMAGE: u32 = 0x5502;
+const GBM_BO_IMPORT_FD: u32 = 0x5503;
+const GBM_BO_IMPORT_FD_MODIFIER: u32 = 0x5504;
+
+const GBM_MAX_PLANES: usize = 4;
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct gbm_import_fd_data {
+    fd: c_int,
+    width: u32,
+    height: u32,
+    stride: u32,
+    format: u32,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct gbm_import_fd_modifier_data {
+    width: u32,
+    height: u32,
+    format: u32,
+    num_fds: u32,
+    fds: [c_int; GBM_MAX_PLANES],
+    strides: [c_int; GBM_MAX_PLANES],
+    offsets: [c_int; GBM_MAX_PLANES],
+    modifier: u64,
 }
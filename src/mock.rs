@@ -0,0 +1,200 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Scripted failures for testing how code handles libgbm errors.
+//!
+//! A ```Mock``` creates software Devices (see ```Device::software()```) whose
+//! calls fail when told to, with a chosen errno, so that error paths which
+//! libgbm only takes under memory pressure can be tested deterministically.
+//!
+//! # Example
+//! ```
+//! extern crate gbm_rs as gbm;
+//! extern crate libc;
+//!
+//! use gbm::mock::Mock;
+//! use gbm::{format, BufferObject, BufferUsage, Operation};
+//!
+//! let mock = Mock::new();
+//! let device = mock.device().unwrap();
+//!
+//! // The second allocation runs out of memory
+//! mock.fail(Operation::CreateBufferObject, 2, libc::ENOMEM);
+//!
+//! let first = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING);
+//! let second = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING);
+//!
+//! assert!(first.is_ok());
+//! assert_eq!(second.err().unwrap().errno(), Some(libc::ENOMEM));
+//! assert_eq!(mock.calls(Operation::CreateBufferObject), 2);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use error::{Error, Operation, Result};
+use software;
+use Device;
+
+/// A script of failures for the Devices created from it
+///
+/// Clones share the same script, so it can be changed while the Devices are
+/// in use. The operations that can fail are ```CreateDevice```,
+/// ```CreateSurface```, ```LockFrontBuffer```, ```CreateBufferObject```,
+/// ```ImportBufferObject```, ```ExportFd```, ```Map``` and ```Write```.
+#[derive(Clone, Debug, Default)]
+pub struct Mock {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    calls: HashMap<Operation, usize>,
+    failures: Vec<Failure>,
+    surface_buffers: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Failure {
+    op: Operation,
+    // The call to fail, counting from the first, or None to fail them all
+    call: Option<usize>,
+    errno: i32,
+}
+
+impl Mock {
+    /// Create a script where nothing fails
+    pub fn new() -> Mock {
+        Mock::default()
+    }
+
+    /// Create a software Device following the script
+    ///
+    /// # Returns
+    ///
+    /// The newly created Device.
+    /// If the script fails ```Operation::CreateDevice``` an ```Error``` will be returned.
+    pub fn device(&self) -> Result<Device> {
+        if let Some(errno) = self.inject(Operation::CreateDevice) {
+            return Err(Error::Failed { op: Operation::CreateDevice, errno: Some(errno) });
+        }
+
+        let device = Device::software()?;
+
+        unsafe { software::attach(device.ptr, self.clone()) }
+
+        Ok(device)
+    }
+
+    /// Fail one upcoming call of an operation
+    ///
+    /// # Arguments
+    ///
+    /// op: The operation to fail
+    ///
+    /// nth: Which of the upcoming calls to fail, 1 for the next one
+    ///
+    /// errno: The errno the call fails with
+    ///
+    /// # Panics
+    ///
+    /// Panics if ```nth``` is 0
+    pub fn fail(&self, op: Operation, nth: usize, errno: i32) {
+        assert!(nth > 0, "calls are counted from 1");
+
+        let mut state = self.lock();
+        let call = state.calls.get(&op).cloned().unwrap_or(0) + nth;
+
+        state.failures.push(Failure { op, call: Some(call), errno });
+    }
+
+    /// Fail every upcoming call of an operation
+    ///
+    /// # Arguments
+    ///
+    /// op: The operation to fail
+    ///
+    /// errno: The errno the calls fail with
+    pub fn fail_always(&self, op: Operation, errno: i32) {
+        self.lock().failures.push(Failure { op, call: None, errno });
+    }
+
+    /// Stop failing an operation
+    ///
+    /// # Arguments
+    ///
+    /// op: The operation to let succeed again
+    pub fn succeed(&self, op: Operation) {
+        self.lock().failures.retain(|failure| failure.op != op);
+    }
+
+    /// Limit the number of buffers each Surface hands out
+    ///
+    /// With a limit of 0, ```has_free_buffers()``` stays false and
+    /// ```lock_front_buffer()``` fails with ```EBUSY```.
+    ///
+    /// # Arguments
+    ///
+    /// count: The number of buffers a Surface can have locked at once
+    pub fn set_surface_buffers(&self, count: usize) {
+        self.lock().surface_buffers = Some(count);
+    }
+
+    /// Get the number of times an operation was attempted
+    ///
+    /// # Returns
+    ///
+    /// The number of calls of the operation so far, including failed ones
+    pub fn calls(&self, op: Operation) -> usize {
+        self.lock().calls.get(&op).cloned().unwrap_or(0)
+    }
+
+    /// Record a call of an operation
+    ///
+    /// # Returns
+    ///
+    /// The errno to fail the call with, or ```None``` if it should succeed
+    pub(crate) fn inject(&self, op: Operation) -> Option<i32> {
+        let mut state = self.lock();
+        let call = {
+            let calls = state.calls.entry(op).or_insert(0);
+            *calls += 1;
+            *calls
+        };
+
+        let index = state.failures.iter().position(|failure| {
+            failure.op == op && failure.call.is_none_or(|nth| nth == call)
+        })?;
+
+        match state.failures[index].call {
+            Some(_) => Some(state.failures.remove(index).errno),
+            None => Some(state.failures[index].errno),
+        }
+    }
+
+    pub(crate) fn surface_buffers(&self) -> Option<usize> {
+        self.lock().surface_buffers
+    }
+
+    // A test panicking while holding the lock shouldn't break the others
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,677 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! DRM format modifiers, describing the memory layout of a buffer.
+//!
+//! The constants and helpers in this module mirror the ```DRM_FORMAT_MOD_*```,
+//! ```I915_FORMAT_MOD_*```, ```AMD_FMT_MOD_*``` and ```AFBC_FORMAT_MOD_*```
+//! definitions of ```drm_fourcc.h```.
+
+use std::fmt;
+
+/// A DRM format modifier
+///
+/// Describes the memory layout of a buffer beyond its format, such as tiling
+/// and compression. The top 8 bits hold the vendor, the remaining 56 bits are
+/// defined by the vendor.
+///
+/// Displays as a human-readable description of the layout.
+///
+/// # Example
+/// ```
+/// extern crate gbm_rs as gbm;
+///
+/// use gbm::modifier::{self, Modifier, Vendor};
+///
+/// let afbc = modifier::arm_afbc(modifier::AFBC_FORMAT_MOD_BLOCK_SIZE_16X16 |
+///                               modifier::AFBC_FORMAT_MOD_SPARSE);
+///
+/// assert_eq!(afbc.vendor(), Vendor::Arm);
+/// assert_eq!(afbc.to_string(), "ARM AFBC (block size 16x16, SPARSE)");
+/// assert_eq!(modifier::I915_X_TILED.to_string(), "I915_X_TILED");
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifier(pub u64);
+
+impl Modifier {
+    /// The buffer uses a linear layout
+    pub const LINEAR: Modifier = Modifier(0);
+    /// The modifier is unknown, or the layout is implicit
+    pub const INVALID: Modifier = Modifier(RESERVED);
+
+    /// Build a modifier from a vendor and a vendor specific value
+    ///
+    /// Analogous to ```fourcc_mod_code()```.
+    pub const fn new(vendor: Vendor, value: u64) -> Modifier {
+        Modifier(((vendor.code() as u64) << 56) | (value & RESERVED))
+    }
+
+    /// Get the vendor of the modifier
+    ///
+    /// # Returns
+    ///
+    /// The vendor that defines the meaning of the modifier
+    pub fn vendor(&self) -> Vendor {
+        Vendor::from_code((self.0 >> 56) as u8)
+    }
+
+    /// Get the vendor specific value of the modifier
+    ///
+    /// # Returns
+    ///
+    /// The lower 56 bits of the modifier
+    pub fn value(&self) -> u64 {
+        self.0 & RESERVED
+    }
+
+    /// Get the name of the modifier
+    ///
+    /// # Returns
+    ///
+    /// The name of the constant in this module for the modifier, such as
+    /// ```I915_Y_TILED```, or ```None``` if there is no constant for it
+    pub fn name(&self) -> Option<&'static str> {
+        NAMES.iter()
+            .find(|&&(_, modifier)| modifier == *self)
+            .map(|&(name, _)| name)
+    }
+}
+
+impl From<u64> for Modifier {
+    fn from(modifier: u64) -> Modifier {
+        Modifier(modifier)
+    }
+}
+
+impl From<Modifier> for u64 {
+    fn from(modifier: Modifier) -> u64 {
+        modifier.0
+    }
+}
+
+impl fmt::Debug for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "Modifier({})", name),
+            None => write!(f, "Modifier({:#018x})", self.0),
+        }
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return f.write_str(name);
+        }
+
+        match self.vendor() {
+            Vendor::Amd => fmt_amd(self.value(), f),
+            Vendor::Arm => fmt_arm(self.value(), f),
+            Vendor::Nvidia => fmt_nvidia(self.value(), f),
+            Vendor::Broadcom => fmt_broadcom(self.value(), f),
+            vendor => write!(f, "{} {:#x}", vendor, self.value()),
+        }
+    }
+}
+
+/// The vendor of a format modifier
+///
+/// Analogous to the ```DRM_FORMAT_MOD_VENDOR_*``` definitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Vendor {
+    /// Vendor independent modifiers such as ```LINEAR``` and ```INVALID```
+    None,
+    /// Intel
+    Intel,
+    /// AMD
+    Amd,
+    /// NVIDIA
+    Nvidia,
+    /// Samsung
+    Samsung,
+    /// Qualcomm
+    Qcom,
+    /// Vivante
+    Vivante,
+    /// Broadcom
+    Broadcom,
+    /// Arm
+    Arm,
+    /// Allwinner
+    Allwinner,
+    /// Amlogic
+    Amlogic,
+    /// MediaTek
+    Mtk,
+    /// Apple
+    Apple,
+    /// A vendor code not known to this crate
+    Unknown(u8),
+}
+
+impl Vendor {
+    /// Get the vendor from its code
+    ///
+    /// # Arguments
+    ///
+    /// code: The top 8 bits of a modifier
+    pub fn from_code(code: u8) -> Vendor {
+        match code {
+            0x00 => Vendor::None,
+            0x01 => Vendor::Intel,
+            0x02 => Vendor::Amd,
+            0x03 => Vendor::Nvidia,
+            0x04 => Vendor::Samsung,
+            0x05 => Vendor::Qcom,
+            0x06 => Vendor::Vivante,
+            0x07 => Vendor::Broadcom,
+            0x08 => Vendor::Arm,
+            0x09 => Vendor::Allwinner,
+            0x0a => Vendor::Amlogic,
+            0x0b => Vendor::Mtk,
+            0x0c => Vendor::Apple,
+            code => Vendor::Unknown(code),
+        }
+    }
+
+    /// Get the code of the vendor
+    ///
+    /// # Returns
+    ///
+    /// The value stored in the top 8 bits of a modifier
+    pub const fn code(&self) -> u8 {
+        match *self {
+            Vendor::None => 0x00,
+            Vendor::Intel => 0x01,
+            Vendor::Amd => 0x02,
+            Vendor::Nvidia => 0x03,
+            Vendor::Samsung => 0x04,
+            Vendor::Qcom => 0x05,
+            Vendor::Vivante => 0x06,
+            Vendor::Broadcom => 0x07,
+            Vendor::Arm => 0x08,
+            Vendor::Allwinner => 0x09,
+            Vendor::Amlogic => 0x0a,
+            Vendor::Mtk => 0x0b,
+            Vendor::Apple => 0x0c,
+            Vendor::Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for Vendor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Vendor::None => f.write_str("NONE"),
+            Vendor::Intel => f.write_str("INTEL"),
+            Vendor::Amd => f.write_str("AMD"),
+            Vendor::Nvidia => f.write_str("NVIDIA"),
+            Vendor::Samsung => f.write_str("SAMSUNG"),
+            Vendor::Qcom => f.write_str("QCOM"),
+            Vendor::Vivante => f.write_str("VIVANTE"),
+            Vendor::Broadcom => f.write_str("BROADCOM"),
+            Vendor::Arm => f.write_str("ARM"),
+            Vendor::Allwinner => f.write_str("ALLWINNER"),
+            Vendor::Amlogic => f.write_str("AMLOGIC"),
+            Vendor::Mtk => f.write_str("MTK"),
+            Vendor::Apple => f.write_str("APPLE"),
+            Vendor::Unknown(code) => write!(f, "vendor {:#04x}", code),
+        }
+    }
+}
+
+const RESERVED: u64 = (1 << 56) - 1;
+
+macro_rules! modifiers {
+    ($($(#[$attr:meta])* $name:ident = $modifier:expr;)*) => {
+        $(
+            $(#[$attr])*
+            pub const $name: Modifier = $modifier;
+        )*
+
+        const NAMES: &[(&str, Modifier)] = &[$((stringify!($name), $name),)*];
+    }
+}
+
+modifiers! {
+    /// The buffer uses a linear layout
+    LINEAR = Modifier::LINEAR;
+    /// The modifier is unknown, or the layout is implicit
+    INVALID = Modifier::INVALID;
+
+    // Intel
+
+    /// Intel X-tiling, 512 byte by 8 row tiles
+    I915_X_TILED = Modifier::new(Vendor::Intel, 1);
+    /// Intel Y-tiling, 128 byte by 32 row tiles
+    I915_Y_TILED = Modifier::new(Vendor::Intel, 2);
+    /// Intel Yf-tiling, 4 KiB tiles whose shape depends on the pixel size
+    I915_YF_TILED = Modifier::new(Vendor::Intel, 3);
+    /// Intel Y-tiling with a render compression control surface plane
+    I915_Y_TILED_CCS = Modifier::new(Vendor::Intel, 4);
+    /// Intel Yf-tiling with a render compression control surface plane
+    I915_YF_TILED_CCS = Modifier::new(Vendor::Intel, 5);
+    /// Intel Gen12 Y-tiling with render compression
+    I915_Y_TILED_GEN12_RC_CCS = Modifier::new(Vendor::Intel, 6);
+    /// Intel Gen12 Y-tiling with media compression
+    I915_Y_TILED_GEN12_MC_CCS = Modifier::new(Vendor::Intel, 7);
+    /// Intel Gen12 Y-tiling with render compression and a clear color plane
+    I915_Y_TILED_GEN12_RC_CCS_CC = Modifier::new(Vendor::Intel, 8);
+    /// Intel Tile4 tiling
+    I915_4_TILED = Modifier::new(Vendor::Intel, 9);
+    /// Intel DG2 Tile4 tiling with render compression
+    I915_4_TILED_DG2_RC_CCS = Modifier::new(Vendor::Intel, 10);
+    /// Intel DG2 Tile4 tiling with media compression
+    I915_4_TILED_DG2_MC_CCS = Modifier::new(Vendor::Intel, 11);
+    /// Intel DG2 Tile4 tiling with render compression and a clear color plane
+    I915_4_TILED_DG2_RC_CCS_CC = Modifier::new(Vendor::Intel, 12);
+    /// Intel Meteor Lake Tile4 tiling with render compression
+    I915_4_TILED_MTL_RC_CCS = Modifier::new(Vendor::Intel, 13);
+    /// Intel Meteor Lake Tile4 tiling with media compression
+    I915_4_TILED_MTL_MC_CCS = Modifier::new(Vendor::Intel, 14);
+    /// Intel Meteor Lake Tile4 tiling with render compression and a clear color plane
+    I915_4_TILED_MTL_RC_CCS_CC = Modifier::new(Vendor::Intel, 15);
+    /// Intel Lunar Lake Tile4 tiling with compression
+    I915_4_TILED_LNL_CCS = Modifier::new(Vendor::Intel, 16);
+    /// Intel Battlemage Tile4 tiling with compression
+    I915_4_TILED_BMG_CCS = Modifier::new(Vendor::Intel, 17);
+
+    // Samsung
+
+    /// Samsung 64 by 32 pixel tiles
+    SAMSUNG_64_32_TILE = Modifier::new(Vendor::Samsung, 1);
+    /// Samsung 16 by 16 pixel tiles
+    SAMSUNG_16_16_TILE = Modifier::new(Vendor::Samsung, 2);
+
+    // Qualcomm
+
+    /// Qualcomm UBWC compressed layout
+    QCOM_COMPRESSED = Modifier::new(Vendor::Qcom, 1);
+    /// Qualcomm tiled layout without compression, used by Adreno 2xx
+    QCOM_TILED2 = Modifier::new(Vendor::Qcom, 2);
+    /// Qualcomm tiled layout without compression, used by Adreno 3xx and later
+    QCOM_TILED3 = Modifier::new(Vendor::Qcom, 3);
+
+    // Vivante
+
+    /// Vivante 4 by 4 pixel tiles
+    VIVANTE_TILED = Modifier::new(Vendor::Vivante, 1);
+    /// Vivante 64 by 64 pixel super-tiles
+    VIVANTE_SUPER_TILED = Modifier::new(Vendor::Vivante, 2);
+    /// Vivante 4 by 4 pixel tiles split between two pixel engines
+    VIVANTE_SPLIT_TILED = Modifier::new(Vendor::Vivante, 3);
+    /// Vivante 64 by 64 pixel super-tiles split between two pixel engines
+    VIVANTE_SPLIT_SUPER_TILED = Modifier::new(Vendor::Vivante, 4);
+
+    // NVIDIA
+
+    /// NVIDIA Tegra tiled layout
+    NVIDIA_TEGRA_TILED = Modifier::new(Vendor::Nvidia, 1);
+    /// NVIDIA 16Bx2 block-linear layout, one GOB high blocks
+    NVIDIA_16BX2_BLOCK_ONE_GOB = nvidia_16bx2_block(0);
+    /// NVIDIA 16Bx2 block-linear layout, two GOB high blocks
+    NVIDIA_16BX2_BLOCK_TWO_GOB = nvidia_16bx2_block(1);
+    /// NVIDIA 16Bx2 block-linear layout, four GOB high blocks
+    NVIDIA_16BX2_BLOCK_FOUR_GOB = nvidia_16bx2_block(2);
+    /// NVIDIA 16Bx2 block-linear layout, eight GOB high blocks
+    NVIDIA_16BX2_BLOCK_EIGHT_GOB = nvidia_16bx2_block(3);
+    /// NVIDIA 16Bx2 block-linear layout, sixteen GOB high blocks
+    NVIDIA_16BX2_BLOCK_SIXTEEN_GOB = nvidia_16bx2_block(4);
+    /// NVIDIA 16Bx2 block-linear layout, thirty-two GOB high blocks
+    NVIDIA_16BX2_BLOCK_THIRTYTWO_GOB = nvidia_16bx2_block(5);
+
+    // Broadcom
+
+    /// Broadcom VC4 T-tiled layout
+    BROADCOM_VC4_T_TILED = Modifier::new(Vendor::Broadcom, 1);
+    /// Broadcom SAND layout with 32 byte wide columns
+    BROADCOM_SAND32 = broadcom_code(2, 0);
+    /// Broadcom SAND layout with 64 byte wide columns
+    BROADCOM_SAND64 = broadcom_code(3, 0);
+    /// Broadcom SAND layout with 128 byte wide columns
+    BROADCOM_SAND128 = broadcom_code(4, 0);
+    /// Broadcom SAND layout with 256 byte wide columns
+    BROADCOM_SAND256 = broadcom_code(5, 0);
+    /// Broadcom VC5 UIF layout
+    BROADCOM_UIF = broadcom_code(6, 0);
+
+    // Arm
+
+    /// Arm 16 by 16 pixel blocks, interleaved in U order
+    ARM_16X16_BLOCK_U_INTERLEAVED = arm_code(ARM_TYPE_MISC, 1);
+
+    // Allwinner
+
+    /// Allwinner 32 by 32 pixel tiles
+    ALLWINNER_TILED = Modifier::new(Vendor::Allwinner, 1);
+}
+
+/// Iterate over the modifiers defined as constants in this module
+///
+/// Modifiers built from parameters, such as AMD and AFBC ones, are not
+/// included.
+///
+/// # Returns
+///
+/// An iterator yielding each modifier constant of this module
+pub fn all() -> impl Iterator<Item = Modifier> {
+    NAMES.iter().map(|&(_, modifier)| modifier)
+}
+
+/// NVIDIA 16Bx2 block-linear layout, the generic layout of Tegra and pre-Turing GPUs
+///
+/// Analogous to ```DRM_FORMAT_MOD_NVIDIA_16BX2_BLOCK()```.
+///
+/// # Arguments
+///
+/// log2_height: The log2 of the block height in GOBs
+pub const fn nvidia_16bx2_block(log2_height: u64) -> Modifier {
+    nvidia_block_linear_2d(0, 0, 0, 0, log2_height)
+}
+
+/// NVIDIA 2D block-linear layout
+///
+/// Analogous to ```DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D()```.
+///
+/// # Arguments
+///
+/// compression: The compression type, 0 if the buffer is not compressed
+///
+/// sector_layout: 1 for the desktop GPU sector layout, 0 for the Tegra one
+///
+/// gob_height: The GOB height and page kind generation, 0 for Fermi to Volta
+/// and Tegra up to Xavier, 2 for Turing and later
+///
+/// kind: The page kind of the buffer
+///
+/// log2_height: The log2 of the block height in GOBs
+pub const fn nvidia_block_linear_2d(compression: u64, sector_layout: u64, gob_height: u64,
+                                    kind: u64, log2_height: u64) -> Modifier {
+    Modifier::new(Vendor::Nvidia,
+                  0x10 |
+                  (log2_height & 0xf) |
+                  ((kind & 0xff) << 12) |
+                  ((gob_height & 0x3) << 20) |
+                  ((sector_layout & 0x1) << 22) |
+                  ((compression & 0x7) << 23))
+}
+
+const fn broadcom_code(value: u64, param: u64) -> Modifier {
+    Modifier::new(Vendor::Broadcom, ((param & 0xffff) << 8) | (value & 0xff))
+}
+
+/// Broadcom SAND layout with an explicit column height
+///
+/// Analogous to ```DRM_FORMAT_MOD_BROADCOM_SAND*_COL_HEIGHT()```.
+///
+/// # Arguments
+///
+/// sand: One of ```BROADCOM_SAND32```, ```BROADCOM_SAND64```,
+/// ```BROADCOM_SAND128``` or ```BROADCOM_SAND256```
+///
+/// column_height: The height of each column in rows
+pub const fn broadcom_sand_col_height(sand: Modifier, column_height: u64) -> Modifier {
+    broadcom_code(sand.0 & 0xff, column_height)
+}
+
+const ARM_TYPE_AFBC: u64 = 0x00;
+const ARM_TYPE_MISC: u64 = 0x01;
+const ARM_TYPE_AFRC: u64 = 0x02;
+
+const fn arm_code(kind: u64, value: u64) -> Modifier {
+    Modifier::new(Vendor::Arm, (kind << 52) | (value & 0x000f_ffff_ffff_ffff))
+}
+
+/// Arm Framebuffer Compression
+///
+/// Analogous to ```DRM_FORMAT_MOD_ARM_AFBC()```.
+///
+/// # Arguments
+///
+/// mode: One of the ```AFBC_FORMAT_MOD_BLOCK_SIZE_*``` values combined with
+/// any of the other ```AFBC_FORMAT_MOD_*``` flags
+pub const fn arm_afbc(mode: u64) -> Modifier {
+    arm_code(ARM_TYPE_AFBC, mode)
+}
+
+/// Mask of the AFBC superblock size
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_MASK: u64 = 0xf;
+/// 16 by 16 pixel AFBC superblocks
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_16X16: u64 = 1;
+/// 32 by 8 pixel AFBC superblocks
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_32X8: u64 = 2;
+/// 64 by 4 pixel AFBC superblocks
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_64X4: u64 = 3;
+/// 32 by 8 pixel superblocks for the luma plane and 64 by 4 for the chroma planes
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_32X8_64X4: u64 = 4;
+/// Lossless color transformation
+pub const AFBC_FORMAT_MOD_YTR: u64 = 1 << 4;
+/// Block split
+pub const AFBC_FORMAT_MOD_SPLIT: u64 = 1 << 5;
+/// Sparse layout, every superblock has the same amount of space reserved
+pub const AFBC_FORMAT_MOD_SPARSE: u64 = 1 << 6;
+/// Copy-block restrict
+pub const AFBC_FORMAT_MOD_CBR: u64 = 1 << 7;
+/// Tiled header
+pub const AFBC_FORMAT_MOD_TILED: u64 = 1 << 8;
+/// Solid color blocks
+pub const AFBC_FORMAT_MOD_SC: u64 = 1 << 9;
+/// Double buffered header
+pub const AFBC_FORMAT_MOD_DB: u64 = 1 << 10;
+/// Buffer content hints
+pub const AFBC_FORMAT_MOD_BCH: u64 = 1 << 11;
+/// Uncompressed storage mode
+pub const AFBC_FORMAT_MOD_USM: u64 = 1 << 12;
+
+/// The base of every AMD modifier, with all fields set to zero
+pub const AMD_FMT_MOD: Modifier = Modifier::new(Vendor::Amd, 0);
+
+/// Shift of the tiling generation field of AMD modifiers
+pub const AMD_FMT_MOD_TILE_VERSION_SHIFT: u32 = 0;
+/// Mask of the tiling generation field of AMD modifiers
+pub const AMD_FMT_MOD_TILE_VERSION_MASK: u64 = 0xff;
+/// Shift of the swizzle mode field of AMD modifiers
+pub const AMD_FMT_MOD_TILE_SHIFT: u32 = 8;
+/// Mask of the swizzle mode field of AMD modifiers
+pub const AMD_FMT_MOD_TILE_MASK: u64 = 0x1f;
+/// Shift of the flag set on AMD modifiers using delta color compression
+pub const AMD_FMT_MOD_DCC_SHIFT: u32 = 13;
+/// Shift of the flag set when an AMD DCC buffer has a displayable retiled copy
+pub const AMD_FMT_MOD_DCC_RETILE_SHIFT: u32 = 14;
+/// Shift of the flag set when the AMD DCC metadata is pipe aligned
+pub const AMD_FMT_MOD_DCC_PIPE_ALIGN_SHIFT: u32 = 15;
+/// Shift of the flag set when AMD DCC blocks are independent at 64 bytes
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_64B_SHIFT: u32 = 16;
+/// Shift of the flag set when AMD DCC blocks are independent at 128 bytes
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_128B_SHIFT: u32 = 17;
+/// Shift of the maximum compressed block size field of AMD DCC modifiers
+pub const AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_SHIFT: u32 = 18;
+/// Mask of the maximum compressed block size field of AMD DCC modifiers
+pub const AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_MASK: u64 = 0x3;
+/// Shift of the flag set when AMD DCC uses constant encoding
+pub const AMD_FMT_MOD_DCC_CONSTANT_ENCODE_SHIFT: u32 = 20;
+/// Shift of the pipe XOR bits field of AMD modifiers
+pub const AMD_FMT_MOD_PIPE_XOR_BITS_SHIFT: u32 = 21;
+/// Shift of the bank XOR bits field of AMD modifiers
+pub const AMD_FMT_MOD_BANK_XOR_BITS_SHIFT: u32 = 24;
+/// Shift of the packers field of AMD modifiers
+pub const AMD_FMT_MOD_PACKERS_SHIFT: u32 = 27;
+/// Shift of the render backends field of AMD modifiers
+pub const AMD_FMT_MOD_RB_SHIFT: u32 = 30;
+/// Shift of the pipes field of AMD modifiers
+pub const AMD_FMT_MOD_PIPE_SHIFT: u32 = 33;
+/// Mask of the pipe XOR bits, bank XOR bits, packers, render backends and
+/// pipes fields of AMD modifiers
+pub const AMD_FMT_MOD_XOR_FIELD_MASK: u64 = 0x7;
+
+/// GFX9 (Vega) tiling generation
+pub const AMD_FMT_MOD_TILE_VER_GFX9: u64 = 1;
+/// GFX10 (Navi 1x) tiling generation
+pub const AMD_FMT_MOD_TILE_VER_GFX10: u64 = 2;
+/// GFX10.3 (Navi 2x) tiling generation
+pub const AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS: u64 = 3;
+/// GFX11 (Navi 3x) tiling generation
+pub const AMD_FMT_MOD_TILE_VER_GFX11: u64 = 4;
+/// GFX12 (Navi 4x) tiling generation
+pub const AMD_FMT_MOD_TILE_VER_GFX12: u64 = 5;
+
+fn amd_field(value: u64, shift: u32, mask: u64) -> u64 {
+    (value >> shift) & mask
+}
+
+fn fmt_amd(value: u64, f: &mut fmt::Formatter) -> fmt::Result {
+    let version = amd_field(value, AMD_FMT_MOD_TILE_VERSION_SHIFT, AMD_FMT_MOD_TILE_VERSION_MASK);
+    let tile = amd_field(value, AMD_FMT_MOD_TILE_SHIFT, AMD_FMT_MOD_TILE_MASK);
+
+    f.write_str("AMD ")?;
+    match version {
+        AMD_FMT_MOD_TILE_VER_GFX9 => f.write_str("GFX9")?,
+        AMD_FMT_MOD_TILE_VER_GFX10 => f.write_str("GFX10")?,
+        AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS => f.write_str("GFX10_RBPLUS")?,
+        AMD_FMT_MOD_TILE_VER_GFX11 => f.write_str("GFX11")?,
+        AMD_FMT_MOD_TILE_VER_GFX12 => f.write_str("GFX12")?,
+        version => write!(f, "tile version {}", version)?,
+    }
+
+    match (version, tile) {
+        (AMD_FMT_MOD_TILE_VER_GFX12, 1) => f.write_str(" 256B_2D")?,
+        (AMD_FMT_MOD_TILE_VER_GFX12, 2) => f.write_str(" 4K_2D")?,
+        (AMD_FMT_MOD_TILE_VER_GFX12, 3) => f.write_str(" 64K_2D")?,
+        (AMD_FMT_MOD_TILE_VER_GFX12, 4) => f.write_str(" 256K_2D")?,
+        (AMD_FMT_MOD_TILE_VER_GFX12, tile) => write!(f, " tile {}", tile)?,
+        (_, 9) => f.write_str(" 64K_S")?,
+        (_, 10) => f.write_str(" 64K_D")?,
+        (_, 25) => f.write_str(" 64K_S_X")?,
+        (_, 26) => f.write_str(" 64K_D_X")?,
+        (_, 27) => f.write_str(" 64K_R_X")?,
+        (_, 31) => f.write_str(" 256K_R_X")?,
+        (_, tile) => write!(f, " tile {}", tile)?,
+    }
+
+    let mut fields = Vec::new();
+
+    if amd_field(value, AMD_FMT_MOD_DCC_SHIFT, 1) != 0 {
+        let max_block = amd_field(value, AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_SHIFT,
+                                  AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_MASK);
+
+        fields.push(format!("DCC max compressed block {}B", 64 << max_block));
+
+        let flags = [
+            (AMD_FMT_MOD_DCC_RETILE_SHIFT, "retile"),
+            (AMD_FMT_MOD_DCC_PIPE_ALIGN_SHIFT, "pipe align"),
+            (AMD_FMT_MOD_DCC_INDEPENDENT_64B_SHIFT, "independent 64B"),
+            (AMD_FMT_MOD_DCC_INDEPENDENT_128B_SHIFT, "independent 128B"),
+            (AMD_FMT_MOD_DCC_CONSTANT_ENCODE_SHIFT, "constant encode"),
+        ];
+
+        for &(shift, name) in flags.iter() {
+            if amd_field(value, shift, 1) != 0 {
+                fields.push(format!("DCC {}", name));
+            }
+        }
+    }
+
+    let counts = [
+        (AMD_FMT_MOD_PIPE_XOR_BITS_SHIFT, "pipe xor bits"),
+        (AMD_FMT_MOD_BANK_XOR_BITS_SHIFT, "bank xor bits"),
+        (AMD_FMT_MOD_PACKERS_SHIFT, "packers"),
+        (AMD_FMT_MOD_RB_SHIFT, "rb"),
+        (AMD_FMT_MOD_PIPE_SHIFT, "pipes"),
+    ];
+
+    for &(shift, name) in counts.iter() {
+        let count = amd_field(value, shift, AMD_FMT_MOD_XOR_FIELD_MASK);
+        if count != 0 {
+            fields.push(format!("{} {}", name, count));
+        }
+    }
+
+    if !fields.is_empty() {
+        write!(f, " ({})", fields.join(", "))?;
+    }
+
+    Ok(())
+}
+
+fn fmt_arm(value: u64, f: &mut fmt::Formatter) -> fmt::Result {
+    let mode = value & 0x000f_ffff_ffff_ffff;
+
+    match value >> 52 {
+        ARM_TYPE_AFBC => {
+            f.write_str("ARM AFBC (block size ")?;
+            match mode & AFBC_FORMAT_MOD_BLOCK_SIZE_MASK {
+                AFBC_FORMAT_MOD_BLOCK_SIZE_16X16 => f.write_str("16x16")?,
+                AFBC_FORMAT_MOD_BLOCK_SIZE_32X8 => f.write_str("32x8")?,
+                AFBC_FORMAT_MOD_BLOCK_SIZE_64X4 => f.write_str("64x4")?,
+                AFBC_FORMAT_MOD_BLOCK_SIZE_32X8_64X4 => f.write_str("32x8_64x4")?,
+                size => write!(f, "{}", size)?,
+            }
+
+            let flags = [
+                (AFBC_FORMAT_MOD_YTR, "YTR"),
+                (AFBC_FORMAT_MOD_SPLIT, "SPLIT"),
+                (AFBC_FORMAT_MOD_SPARSE, "SPARSE"),
+                (AFBC_FORMAT_MOD_CBR, "CBR"),
+                (AFBC_FORMAT_MOD_TILED, "TILED"),
+                (AFBC_FORMAT_MOD_SC, "SC"),
+                (AFBC_FORMAT_MOD_DB, "DB"),
+                (AFBC_FORMAT_MOD_BCH, "BCH"),
+                (AFBC_FORMAT_MOD_USM, "USM"),
+            ];
+
+            for &(flag, name) in flags.iter() {
+                if mode & flag != 0 {
+                    write!(f, ", {}", name)?;
+                }
+            }
+
+            f.write_str(")")
+        }
+        ARM_TYPE_AFRC => write!(f, "ARM AFRC {:#x}", mode),
+        ARM_TYPE_MISC => write!(f, "ARM {:#x}", mode),
+        kind => write!(f, "ARM type {} {:#x}", kind, mode),
+    }
+}
+
+fn fmt_nvidia(value: u64, f: &mut fmt::Formatter) -> fmt::Result {
+    if value & 0x10 == 0 {
+        return write!(f, "NVIDIA {:#x}", value);
+    }
+
+    write!(f, "NVIDIA block-linear 2D (block height {} GOBs, kind {:#x}, GOB height {}, \
+               sector layout {}, compression {})",
+           1 << (value & 0xf),
+           (value >> 12) & 0xff,
+           (value >> 20) & 0x3,
+           if (value >> 22) & 0x1 != 0 { "desktop" } else { "Tegra" },
+           (value >> 23) & 0x7)
+}
+
+fn fmt_broadcom(value: u64, f: &mut fmt::Formatter) -> fmt::Result {
+    let base = Modifier::new(Vendor::Broadcom, value & 0xff);
+    let param = (value >> 8) & 0xffff;
+
+    match base.name() {
+        Some(name) if param != 0 => write!(f, "{} (column height {})", name, param),
+        _ => write!(f, "BROADCOM {:#x}", value),
+    }
+}
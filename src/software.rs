@@ -0,0 +1,739 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! A libgbm stand-in implemented in Rust, allocating buffers from memfds.
+//!
+//! Buffers always use a linear layout. Strides are aligned to 64 bytes and
+//! the planes of a buffer are laid out one after another in a single memfd.
+//! The fds exported are memfds rather than dma-bufs, so they can be mapped
+//! and imported back, but not handed to a GPU.
+
+use libc::{self, c_char, c_int, c_uint, c_void, size_t};
+use std::os::unix::prelude::*;
+use std::mem;
+use std::ptr;
+
+use error::Operation;
+use ffi::Api;
+use format::{Format, FormatInfo};
+use {BufferUsage, Modifier};
+#[cfg(feature = "mock")]
+use mock::Mock;
+use {gbm_bo, gbm_device, gbm_surface, gbm_import_fd_data, gbm_import_fd_modifier_data};
+use {GBM_BO_IMPORT_FD, GBM_BO_IMPORT_FD_MODIFIER};
+
+/// The function table of the software backend
+pub(crate) static API: Api = Api {
+    gbm_device_get_backend_name: device_get_backend_name,
+    gbm_device_is_format_supported: device_is_format_supported,
+    gbm_device_destroy: device_destroy,
+    gbm_create_device: create_device,
+    gbm_bo_create: bo_create,
+    gbm_bo_import: bo_import,
+    gbm_bo_get_width: bo_get_width,
+    gbm_bo_get_height: bo_get_height,
+    gbm_bo_get_stride: bo_get_stride,
+    gbm_bo_get_format: bo_get_format,
+    gbm_bo_get_handle: bo_get_handle,
+    gbm_bo_get_fd: bo_get_fd,
+    gbm_bo_write: bo_write,
+    gbm_bo_set_user_data: bo_set_user_data,
+    gbm_bo_get_user_data: bo_get_user_data,
+    gbm_bo_destroy: bo_destroy,
+    gbm_surface_create: surface_create,
+    gbm_surface_lock_front_buffer: surface_lock_front_buffer,
+    gbm_surface_release_buffer: surface_release_buffer,
+    gbm_surface_has_free_buffers: surface_has_free_buffers,
+    gbm_surface_destroy: surface_destroy,
+    gbm_device_get_format_modifier_plane_count: Some(device_get_format_modifier_plane_count),
+    gbm_bo_create_with_modifiers: Some(bo_create_with_modifiers),
+    gbm_bo_create_with_modifiers2: Some(bo_create_with_modifiers2),
+    gbm_bo_get_modifier: Some(bo_get_modifier),
+    gbm_bo_get_plane_count: Some(bo_get_plane_count),
+    gbm_bo_get_offset: Some(bo_get_offset),
+    gbm_bo_get_stride_for_plane: Some(bo_get_stride_for_plane),
+    gbm_bo_get_handle_for_plane: Some(bo_get_handle_for_plane),
+    gbm_bo_get_fd_for_plane: Some(bo_get_fd_for_plane),
+    gbm_bo_get_bpp: Some(bo_get_bpp),
+    gbm_bo_map: Some(bo_map),
+    gbm_bo_unmap: Some(bo_unmap),
+    gbm_surface_create_with_modifiers: Some(surface_create_with_modifiers),
+    gbm_surface_create_with_modifiers2: Some(surface_create_with_modifiers2),
+};
+
+/// Strides are aligned to this many bytes, like most hardware requires
+const STRIDE_ALIGN: u32 = 64;
+
+/// The number of buffers a surface hands out before running dry
+const SURFACE_BUFFERS: usize = 4;
+
+const GBM_BO_TRANSFER_READ: u32 = 1 << 0;
+const GBM_BO_TRANSFER_WRITE: u32 = 1 << 1;
+
+struct Device {
+    // Kept for debugging, nothing is allocated through it
+    _fd: c_int,
+    #[cfg(feature = "mock")]
+    mock: Option<Mock>,
+}
+
+struct Plane {
+    fd: OwnedFd,
+    offset: u32,
+    stride: u32,
+}
+
+struct Bo {
+    dev: *const Device,
+    width: u32,
+    height: u32,
+    format: Format,
+    usage: BufferUsage,
+    planes: Vec<Plane>,
+    user_data: *mut c_void,
+    destroy_user_data: Option<extern "C" fn(bo: *const gbm_bo, data: *mut c_void)>,
+}
+
+struct Surface {
+    dev: *const Device,
+    width: u32,
+    height: u32,
+    format: Format,
+    usage: BufferUsage,
+    // Each buffer allocated so far and whether it is locked
+    buffers: Vec<(*mut Bo, bool)>,
+}
+
+struct Mapping {
+    addr: *mut c_void,
+    len: size_t,
+}
+
+unsafe fn set_errno(errno: c_int) {
+    *libc::__errno_location() = errno;
+}
+
+/// Check whether a call should fail, setting errno if it should
+#[cfg(feature = "mock")]
+unsafe fn inject(dev: *const Device, op: Operation) -> bool {
+    let errno = match (*dev).mock {
+        Some(ref mock) => mock.inject(op),
+        None => None,
+    };
+
+    match errno {
+        Some(errno) => {
+            set_errno(errno);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(not(feature = "mock"))]
+unsafe fn inject(_dev: *const Device, _op: Operation) -> bool {
+    false
+}
+
+#[cfg(feature = "mock")]
+unsafe fn surface_buffers(dev: *const Device) -> usize {
+    match (*dev).mock {
+        Some(ref mock) => mock.surface_buffers().unwrap_or(SURFACE_BUFFERS),
+        None => SURFACE_BUFFERS,
+    }
+}
+
+#[cfg(not(feature = "mock"))]
+unsafe fn surface_buffers(_dev: *const Device) -> usize {
+    SURFACE_BUFFERS
+}
+
+/// Script the failures of a software Device
+#[cfg(feature = "mock")]
+pub(crate) unsafe fn attach(gbm: *const gbm_device, mock: Mock) {
+    (*(gbm as *mut Device)).mock = Some(mock);
+}
+
+fn align(value: u32, alignment: u32) -> Option<u32> {
+    value.div_ceil(alignment).checked_mul(alignment)
+}
+
+/// Pick the modifier to allocate with, only linear buffers are supported
+unsafe fn pick_modifier(modifiers: *const u64, count: c_uint) -> bool {
+    if modifiers.is_null() {
+        return false;
+    }
+
+    std::slice::from_raw_parts(modifiers, count as usize)
+        .iter()
+        .any(|&modifier| modifier == Modifier::LINEAR.0 || modifier == Modifier::INVALID.0)
+}
+
+fn supported(format: u32, usage: u32) -> Option<FormatInfo> {
+    let usage = BufferUsage::from_bits_truncate(usage);
+
+    // There is nothing to keep protected buffers away from the CPU
+    if usage.contains(BufferUsage::PROTECTED) {
+        return None;
+    }
+
+    Format(format).info()
+}
+
+unsafe fn as_bo<'a>(bo: *const gbm_bo) -> &'a mut Bo {
+    &mut *(bo as *mut Bo)
+}
+
+unsafe fn as_surface<'a>(surface: *const gbm_surface) -> &'a mut Surface {
+    &mut *(surface as *mut Surface)
+}
+
+/// Get the size of the file behind an fd, without moving its shared offset
+fn file_size(fd: BorrowedFd) -> Option<u64> {
+    let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+
+    if unsafe { libc::fstat(fd.as_raw_fd(), &mut stat) } != 0 {
+        return None;
+    }
+
+    Some(stat.st_size as u64)
+}
+
+fn dup(fd: BorrowedFd) -> c_int {
+    match fd.try_clone_to_owned() {
+        Ok(fd) => fd.into_raw_fd(),
+        Err(_) => -1,
+    }
+}
+
+/// Allocate a linear buffer in a new memfd
+unsafe fn allocate(dev: *const Device, width: u32, height: u32,
+                   format: u32, usage: u32) -> *mut Bo {
+    let info = match supported(format, usage) {
+        Some(info) if width > 0 && height > 0 => info,
+        _ => {
+            set_errno(libc::EINVAL);
+            return ptr::null_mut();
+        }
+    };
+
+    let mut layout = Vec::new();
+    let mut size: u64 = 0;
+
+    for plane in 0..info.planes as usize {
+        let stride = match info.min_stride(plane, width).and_then(|min| align(min, STRIDE_ALIGN)) {
+            Some(stride) => stride,
+            None => {
+                set_errno(libc::EINVAL);
+                return ptr::null_mut();
+            }
+        };
+
+        layout.push((size as u32, stride));
+        size += stride as u64 * info.plane_height(plane, height) as u64;
+
+        // Offsets of the next plane have to fit in a u32 as well
+        if size > u32::MAX as u64 {
+            set_errno(libc::EINVAL);
+            return ptr::null_mut();
+        }
+    }
+
+    let fd = libc::memfd_create(b"gbm-software\0".as_ptr() as *const c_char,
+                                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING);
+
+    if fd < 0 {
+        return ptr::null_mut();
+    }
+
+    let fd = OwnedFd::from_raw_fd(fd);
+
+    if libc::ftruncate(fd.as_raw_fd(), size as libc::off_t) != 0 {
+        return ptr::null_mut();
+    }
+
+    // Importers can map the whole buffer without it being truncated under them
+    libc::fcntl(fd.as_raw_fd(), libc::F_ADD_SEALS, libc::F_SEAL_SHRINK | libc::F_SEAL_GROW);
+
+    let mut planes = Vec::new();
+
+    for (offset, stride) in layout {
+        let fd = match fd.try_clone() {
+            Ok(fd) => fd,
+            Err(_) => return ptr::null_mut(),
+        };
+
+        planes.push(Plane { fd, offset, stride });
+    }
+
+    Box::into_raw(Box::new(Bo {
+        dev,
+        width,
+        height,
+        format: Format(format),
+        usage: BufferUsage::from_bits_truncate(usage),
+        planes,
+        user_data: ptr::null_mut(),
+        destroy_user_data: None,
+    }))
+}
+
+unsafe fn destroy(bo: *mut Bo) {
+    let bo = Box::from_raw(bo);
+
+    if let Some(destroy_user_data) = bo.destroy_user_data {
+        destroy_user_data(&*bo as *const Bo as *const gbm_bo, bo.user_data);
+    }
+}
+
+unsafe extern "C" fn create_device(fd: c_int) -> *const gbm_device {
+    Box::into_raw(Box::new(Device {
+        _fd: fd,
+        #[cfg(feature = "mock")]
+        mock: None,
+    })) as *const gbm_device
+}
+
+unsafe extern "C" fn device_destroy(gbm: *const gbm_device) {
+    drop(Box::from_raw(gbm as *mut Device));
+}
+
+unsafe extern "C" fn device_get_backend_name(_gbm: *const gbm_device) -> *const c_char {
+    b"software\0".as_ptr() as *const c_char
+}
+
+unsafe extern "C" fn device_is_format_supported(_gbm: *const gbm_device,
+                                                format: u32, usage: u32) -> c_int {
+    supported(format, usage).is_some() as c_int
+}
+
+unsafe extern "C" fn device_get_format_modifier_plane_count(_gbm: *const gbm_device,
+                                                            format: u32, modifier: u64) -> c_int {
+    match Format(format).info() {
+        Some(info) if modifier == Modifier::LINEAR.0 => info.planes as c_int,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn bo_create(gbm: *const gbm_device,
+                               width: u32, height: u32,
+                               format: u32, flags: u32) -> *const gbm_bo {
+    let dev = gbm as *const Device;
+
+    if inject(dev, Operation::CreateBufferObject) {
+        return ptr::null();
+    }
+
+    allocate(dev, width, height, format, flags) as *const gbm_bo
+}
+
+unsafe extern "C" fn bo_create_with_modifiers(gbm: *const gbm_device,
+                                              width: u32, height: u32, format: u32,
+                                              modifiers: *const u64, count: c_uint) -> *const gbm_bo {
+    let usage = BufferUsage::SCANOUT | BufferUsage::RENDERING;
+
+    bo_create_with_modifiers2(gbm, width, height, format, modifiers, count, usage.bits())
+}
+
+unsafe extern "C" fn bo_create_with_modifiers2(gbm: *const gbm_device,
+                                               width: u32, height: u32, format: u32,
+                                               modifiers: *const u64, count: c_uint,
+                                               flags: u32) -> *const gbm_bo {
+    if !pick_modifier(modifiers, count) {
+        set_errno(libc::EINVAL);
+        return ptr::null();
+    }
+
+    bo_create(gbm, width, height, format, flags)
+}
+
+unsafe extern "C" fn bo_import(gbm: *const gbm_device, _type: u32,
+                               buffer: *const c_void, usage: u32) -> *const gbm_bo {
+    let dev = gbm as *const Device;
+
+    if inject(dev, Operation::ImportBufferObject) {
+        return ptr::null();
+    }
+
+    let (width, height, format, planes) = match _type {
+        GBM_BO_IMPORT_FD => {
+            let data = &*(buffer as *const gbm_import_fd_data);
+            let plane = (data.fd, 0, data.stride as c_int);
+
+            (data.width, data.height, data.format, vec![plane])
+        }
+        GBM_BO_IMPORT_FD_MODIFIER => {
+            let data = &*(buffer as *const gbm_import_fd_modifier_data);
+
+            if data.modifier != Modifier::LINEAR.0 && data.modifier != Modifier::INVALID.0 {
+                set_errno(libc::EINVAL);
+                return ptr::null();
+            }
+
+            let planes = (0..data.num_fds as usize)
+                .map(|i| (data.fds[i], data.offsets[i], data.strides[i]))
+                .collect();
+
+            (data.width, data.height, data.format, planes)
+        }
+        // There are no Wayland or EGL buffers to import
+        _ => {
+            set_errno(libc::ENOSYS);
+            return ptr::null();
+        }
+    };
+
+    let info = match supported(format, usage) {
+        Some(info) if info.planes as usize == planes.len() && width > 0 && height > 0 => info,
+        _ => {
+            set_errno(libc::EINVAL);
+            return ptr::null();
+        }
+    };
+
+    let mut imported = Vec::new();
+
+    for (i, (fd, offset, stride)) in planes.into_iter().enumerate() {
+        if fd < 0 || offset < 0 || stride <= 0 || info.min_stride(i, width).is_none_or(|min| (stride as u32) < min) {
+            set_errno(libc::EINVAL);
+            return ptr::null();
+        }
+
+        let fd = BorrowedFd::borrow_raw(fd);
+
+        // Mapping trusts the layout, so every plane has to fit in its file
+        let end = offset as u64 + stride as u64 * info.plane_height(i, height) as u64;
+
+        if file_size(fd).is_none_or(|size| size < end) {
+            set_errno(libc::EINVAL);
+            return ptr::null();
+        }
+
+        let fd = match fd.try_clone_to_owned() {
+            Ok(fd) => fd,
+            Err(_) => return ptr::null(),
+        };
+
+        imported.push(Plane { fd, offset: offset as u32, stride: stride as u32 });
+    }
+
+    Box::into_raw(Box::new(Bo {
+        dev,
+        width,
+        height,
+        format: Format(format),
+        usage: BufferUsage::from_bits_truncate(usage),
+        planes: imported,
+        user_data: ptr::null_mut(),
+        destroy_user_data: None,
+    })) as *const gbm_bo
+}
+
+unsafe extern "C" fn bo_get_width(bo: *const gbm_bo) -> u32 {
+    as_bo(bo).width
+}
+
+unsafe extern "C" fn bo_get_height(bo: *const gbm_bo) -> u32 {
+    as_bo(bo).height
+}
+
+unsafe extern "C" fn bo_get_stride(bo: *const gbm_bo) -> u32 {
+    as_bo(bo).planes[0].stride
+}
+
+unsafe extern "C" fn bo_get_format(bo: *const gbm_bo) -> u32 {
+    as_bo(bo).format.0
+}
+
+unsafe extern "C" fn bo_get_modifier(_bo: *const gbm_bo) -> u64 {
+    Modifier::LINEAR.0
+}
+
+unsafe extern "C" fn bo_get_bpp(bo: *const gbm_bo) -> u32 {
+    match as_bo(bo).format.info() {
+        Some(info) => info.cpp[0] as u32 * 8,
+        None => 0,
+    }
+}
+
+// There are no GEM handles, the fd of the plane identifies it instead
+unsafe extern "C" fn bo_get_handle(bo: *const gbm_bo) -> u64 {
+    as_bo(bo).planes[0].fd.as_raw_fd() as u64
+}
+
+unsafe extern "C" fn bo_get_fd(bo: *const gbm_bo) -> c_int {
+    let bo = as_bo(bo);
+
+    if inject(bo.dev, Operation::ExportFd) {
+        return -1;
+    }
+
+    dup(bo.planes[0].fd.as_fd())
+}
+
+unsafe extern "C" fn bo_get_plane_count(bo: *const gbm_bo) -> c_int {
+    as_bo(bo).planes.len() as c_int
+}
+
+unsafe extern "C" fn bo_get_offset(bo: *const gbm_bo, plane: c_int) -> u32 {
+    as_bo(bo).planes.get(plane as usize).map_or(0, |plane| plane.offset)
+}
+
+unsafe extern "C" fn bo_get_stride_for_plane(bo: *const gbm_bo, plane: c_int) -> u32 {
+    as_bo(bo).planes.get(plane as usize).map_or(0, |plane| plane.stride)
+}
+
+unsafe extern "C" fn bo_get_handle_for_plane(bo: *const gbm_bo, plane: c_int) -> u64 {
+    as_bo(bo).planes.get(plane as usize).map_or(0, |plane| plane.fd.as_raw_fd() as u64)
+}
+
+unsafe extern "C" fn bo_get_fd_for_plane(bo: *const gbm_bo, plane: c_int) -> c_int {
+    let bo = as_bo(bo);
+
+    if inject(bo.dev, Operation::ExportFd) {
+        return -1;
+    }
+
+    match bo.planes.get(plane as usize) {
+        Some(plane) => dup(plane.fd.as_fd()),
+        None => {
+            set_errno(libc::EINVAL);
+            -1
+        }
+    }
+}
+
+unsafe extern "C" fn bo_write(bo: *const gbm_bo, buf: *const c_void, count: size_t) -> c_int {
+    let bo = as_bo(bo);
+
+    if inject(bo.dev, Operation::Write) {
+        return -1;
+    }
+
+    // Like libgbm, only buffers meant to be written to can be
+    if !bo.usage.contains(BufferUsage::WRITE) {
+        set_errno(libc::EINVAL);
+        return -1;
+    }
+
+    let info = match bo.format.info() {
+        Some(info) => info,
+        None => {
+            set_errno(libc::EINVAL);
+            return -1;
+        }
+    };
+
+    // The data is laid out like the buffer, imported planes may each live
+    // in their own fd so every plane gets its own range of it
+    for (i, plane) in bo.planes.iter().enumerate() {
+        let start = plane.offset as usize;
+        let end = start + plane.stride as usize * info.plane_height(i, bo.height) as usize;
+
+        if start >= count {
+            continue;
+        }
+
+        let len = end.min(count) - start;
+        let written = libc::pwrite(plane.fd.as_raw_fd(), (buf as *const u8).add(start) as *const c_void,
+                                   len, start as libc::off_t);
+
+        if written < 0 {
+            return -1;
+        }
+
+        if written as size_t != len {
+            set_errno(libc::EIO);
+            return -1;
+        }
+    }
+
+    0
+}
+
+unsafe extern "C" fn bo_map(bo: *const gbm_bo, x: u32, y: u32, width: u32, height: u32,
+                            flags: u32, stride: *mut u32, map_data: *mut *mut c_void) -> *mut c_void {
+    let bo = as_bo(bo);
+    let plane = &bo.planes[0];
+    let cpp = bo_get_bpp(bo as *const Bo as *const gbm_bo).div_ceil(8);
+
+    if inject(bo.dev, Operation::Map) {
+        return ptr::null_mut();
+    }
+
+    if x.checked_add(width).is_none_or(|right| right > bo.width) ||
+        y.checked_add(height).is_none_or(|bottom| bottom > bo.height) {
+        set_errno(libc::EINVAL);
+        return ptr::null_mut();
+    }
+
+    let mut prot = 0;
+    if flags & GBM_BO_TRANSFER_READ != 0 {
+        prot |= libc::PROT_READ;
+    }
+    if flags & GBM_BO_TRANSFER_WRITE != 0 {
+        prot |= libc::PROT_WRITE;
+    }
+
+    let len = match file_size(plane.fd.as_fd()) {
+        Some(len) if len > 0 => len,
+        _ => {
+            set_errno(libc::EINVAL);
+            return ptr::null_mut();
+        }
+    };
+
+    let addr = libc::mmap(ptr::null_mut(), len as size_t, prot, libc::MAP_SHARED,
+                          plane.fd.as_raw_fd(), 0);
+
+    if addr == libc::MAP_FAILED {
+        return ptr::null_mut();
+    }
+
+    *stride = plane.stride;
+    *map_data = Box::into_raw(Box::new(Mapping { addr, len: len as size_t })) as *mut c_void;
+
+    let offset = plane.offset as usize + y as usize * plane.stride as usize + (x * cpp) as usize;
+
+    (addr as *mut u8).add(offset) as *mut c_void
+}
+
+unsafe extern "C" fn bo_unmap(_bo: *const gbm_bo, map_data: *mut c_void) {
+    let mapping = Box::from_raw(map_data as *mut Mapping);
+
+    libc::munmap(mapping.addr, mapping.len);
+}
+
+unsafe extern "C" fn bo_set_user_data(bo: *const gbm_bo, data: *mut c_void,
+                                      destroy_user_data: Option<extern "C" fn(bo: *const gbm_bo, data: *mut c_void)>) {
+    let bo = as_bo(bo);
+
+    bo.user_data = data;
+    bo.destroy_user_data = destroy_user_data;
+}
+
+unsafe extern "C" fn bo_get_user_data(bo: *const gbm_bo) -> *mut c_void {
+    as_bo(bo).user_data
+}
+
+unsafe extern "C" fn bo_destroy(bo: *const gbm_bo) {
+    destroy(bo as *mut Bo);
+}
+
+unsafe extern "C" fn surface_create(gbm: *const gbm_device,
+                                    width: u32, height: u32,
+                                    format: u32, flags: u32) -> *const gbm_surface {
+    let dev = gbm as *const Device;
+
+    if inject(dev, Operation::CreateSurface) {
+        return ptr::null();
+    }
+
+    if supported(format, flags).is_none() || width == 0 || height == 0 {
+        set_errno(libc::EINVAL);
+        return ptr::null();
+    }
+
+    Box::into_raw(Box::new(Surface {
+        dev,
+        width,
+        height,
+        format: Format(format),
+        usage: BufferUsage::from_bits_truncate(flags),
+        buffers: Vec::new(),
+    })) as *const gbm_surface
+}
+
+unsafe extern "C" fn surface_create_with_modifiers(gbm: *const gbm_device,
+                                                   width: u32, height: u32, format: u32,
+                                                   modifiers: *const u64,
+                                                   count: c_uint) -> *const gbm_surface {
+    let usage = BufferUsage::SCANOUT | BufferUsage::RENDERING;
+
+    surface_create_with_modifiers2(gbm, width, height, format, modifiers, count, usage.bits())
+}
+
+unsafe extern "C" fn surface_create_with_modifiers2(gbm: *const gbm_device,
+                                                    width: u32, height: u32, format: u32,
+                                                    modifiers: *const u64, count: c_uint,
+                                                    flags: u32) -> *const gbm_surface {
+    if !pick_modifier(modifiers, count) {
+        set_errno(libc::EINVAL);
+        return ptr::null();
+    }
+
+    surface_create(gbm, width, height, format, flags)
+}
+
+// There is no renderer, so the front buffer is whichever buffer is free
+unsafe extern "C" fn surface_lock_front_buffer(surface: *const gbm_surface) -> *const gbm_bo {
+    let surface = as_surface(surface);
+
+    if inject(surface.dev, Operation::LockFrontBuffer) {
+        return ptr::null();
+    }
+
+    if !has_free_buffers(surface) {
+        set_errno(libc::EBUSY);
+        return ptr::null();
+    }
+
+    if let Some(&mut (bo, ref mut locked)) = surface.buffers.iter_mut().find(|&&mut (_, locked)| !locked) {
+        *locked = true;
+        return bo as *const gbm_bo;
+    }
+
+    let bo = allocate(surface.dev, surface.width, surface.height,
+                      surface.format.0, surface.usage.bits());
+
+    if !bo.is_null() {
+        surface.buffers.push((bo, true));
+    }
+
+    bo as *const gbm_bo
+}
+
+unsafe extern "C" fn surface_release_buffer(surface: *const gbm_surface, bo: *const gbm_bo) {
+    let surface = as_surface(surface);
+
+    if let Some(&mut (_, ref mut locked)) = surface.buffers.iter_mut()
+        .find(|&&mut (buffer, _)| buffer as *const gbm_bo == bo) {
+        *locked = false;
+    }
+}
+
+unsafe fn has_free_buffers(surface: &Surface) -> bool {
+    let limit = surface_buffers(surface.dev);
+    let locked = surface.buffers.iter().filter(|&&(_, locked)| locked).count();
+
+    locked < limit && (surface.buffers.len() < limit || locked < surface.buffers.len())
+}
+
+unsafe extern "C" fn surface_has_free_buffers(surface: *const gbm_surface) -> c_int {
+    has_free_buffers(as_surface(surface)) as c_int
+}
+
+unsafe extern "C" fn surface_destroy(surface: *const gbm_surface) {
+    let surface = Box::from_raw(surface as *mut Surface);
+
+    for &(bo, _) in &surface.buffers {
+        destroy(bo);
+    }
+}
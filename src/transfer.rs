@@ -0,0 +1,93 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! How a mapped BufferObject is going to be accessed.
+//!
+//! Each kind of access is a type, passed to ```BufferObject::map()```, so the
+//! returned ```Mapping``` only allows what it was mapped for. Read mappings
+//! dereference to ```&[u8]```, read-write ones to ```&mut [u8]``` as well, and
+//! write-only ones, whose contents may be uninitialized or uncached, can
+//! only be copied into.
+//!
+//! # Example
+//! ```
+//! extern crate gbm_rs as gbm;
+//!
+//! use gbm::{format, transfer, BufferObject, BufferUsage};
+//!
+//! let device = gbm::Device::software().unwrap();
+//! let mut buffer = BufferObject::new(&device, 64, 64, format::R8,
+//!                                    BufferUsage::RENDERING).unwrap();
+//!
+//! buffer.map(0, 0, 64, 64, transfer::Write).unwrap().copy_from_slice(0, &[0xff; 64]);
+//!
+//! assert_eq!(buffer.map(0, 0, 64, 1, transfer::Read).unwrap()[..], [0xff; 64][..]);
+//! ```
+
+/// A kind of access to a mapped BufferObject
+///
+/// Implemented by ```Read```, ```Write``` and ```ReadWrite``` only.
+pub trait Transfer: private::Sealed {
+    /// The ```GBM_BO_TRANSFER_*``` flags to map with
+    const FLAGS: u32;
+}
+
+/// The mapping is only read from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Read;
+
+/// The mapping is only written to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Write;
+
+/// The mapping is read from and written to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ReadWrite;
+
+impl Transfer for Read {
+    const FLAGS: u32 = 1 << 0;
+}
+
+impl Transfer for Write {
+    const FLAGS: u32 = 1 << 1;
+}
+
+impl Transfer for ReadWrite {
+    const FLAGS: u32 = (1 << 0) | (1 << 1);
+}
+
+/// A kind of access allowing the mapping to be read
+pub trait Readable: Transfer {}
+
+/// A kind of access allowing the mapping to be written
+pub trait Writable: Transfer {}
+
+impl Readable for Read {}
+impl Readable for ReadWrite {}
+impl Writable for Write {}
+impl Writable for ReadWrite {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Read {}
+    impl Sealed for super::Write {}
+    impl Sealed for super::ReadWrite {}
+}
@@ -0,0 +1,503 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+extern crate gbm_rs as gbm;
+extern crate libc;
+
+mod common;
+
+use gbm::{format, transfer, BufferObject, BufferUsage, Error, Format, ImportPlane,
+          ImportSource, Modifier, Operation};
+use std::cell::Cell;
+use std::os::unix::prelude::*;
+use std::rc::Rc;
+
+#[test]
+fn allocate_every_format() {
+    let device = common::device();
+    let mut allocated = 0;
+
+    for format in format::all() {
+        if !device.is_format_supported(format, BufferUsage::RENDERING) {
+            continue;
+        }
+
+        let bo = match BufferObject::new(&device, 67, 33, format, BufferUsage::RENDERING) {
+            Ok(bo) => bo,
+            // Drivers may not allocate every format they claim to support
+            Err(_) if !common::is_software(&device) => continue,
+            Err(err) => panic!("{}: {}", format, err),
+        };
+        let info = format.info().unwrap();
+
+        assert_eq!(bo.width(), 67);
+        assert_eq!(bo.height(), 33);
+        assert_eq!(bo.format(), format);
+        assert!(bo.plane_count() >= info.planes, "{}", format);
+
+        for plane in bo.planes().take(info.planes as usize) {
+            let index = plane.index() as usize;
+
+            assert!(plane.stride() >= info.min_stride(index, 67).unwrap(), "{} plane {}", format, index);
+        }
+
+        allocated += 1;
+    }
+
+    assert!(allocated > 0);
+}
+
+#[test]
+fn software_layout() {
+    let device = common::software();
+
+    for format in format::all() {
+        let bo = BufferObject::new(&device, 67, 33, format, BufferUsage::RENDERING).unwrap();
+        let info = format.info().unwrap();
+
+        assert_eq!(bo.modifier(), Modifier::LINEAR);
+        assert_eq!(bo.plane_count(), info.planes);
+        assert_eq!(bo.planes().len(), info.planes as usize);
+        assert_eq!(bo.stride(), bo.planes().next().unwrap().stride());
+
+        // Planes are aligned and follow each other without overlapping
+        let mut end = 0;
+        for plane in bo.planes() {
+            let index = plane.index() as usize;
+
+            assert_eq!(plane.stride() % 64, 0);
+            assert_eq!(plane.offset(), end, "{} plane {}", format, index);
+            end = plane.offset() + plane.stride() * info.plane_height(index, 33);
+        }
+    }
+}
+
+#[test]
+fn allocate_invalid() {
+    let device = common::device();
+
+    for &(width, height, format) in &[(0, 64, format::XRGB8888), (64, 64, Format(0))] {
+        match BufferObject::new(&device, width, height, format, BufferUsage::RENDERING) {
+            Err(err) => assert_eq!(err.op(), Some(Operation::CreateBufferObject)),
+            Ok(_) => panic!("allocated a {}x{} {} buffer", width, height, format),
+        }
+    }
+}
+
+#[test]
+fn with_modifiers() {
+    let device = common::device();
+    let bo = BufferObject::with_modifiers(&device, 64, 64, format::XRGB8888,
+                                          &[Modifier::LINEAR], BufferUsage::RENDERING).unwrap();
+
+    assert_eq!(bo.modifier(), Modifier::LINEAR);
+}
+
+#[test]
+fn with_unsupported_modifiers() {
+    let device = common::software();
+    let err = BufferObject::with_modifiers(&device, 64, 64, format::XRGB8888,
+                                           &[gbm::modifier::I915_Y_TILED],
+                                           BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateBufferObject));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn device() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+
+    assert!(std::ptr::eq(bo.device(), &device));
+}
+
+#[test]
+fn handles() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+
+    assert_eq!(bo.handle_u32() as u64, bo.handle_u64() & 0xffff_ffff);
+    assert_eq!(bo.handle_i64(), bo.handle_u64() as i64);
+    assert_eq!(bo.handle_i32(), bo.handle_u32() as i32);
+    assert_eq!(bo.handle_ptr() as u64, bo.handle_u64());
+}
+
+#[test]
+fn write_and_read_back() {
+    let device = common::device();
+    let mut bo = BufferObject::new(&device, 64, 64, format::ARGB8888,
+                                   BufferUsage::CURSOR | BufferUsage::WRITE).unwrap();
+    let stride = bo.stride() as usize;
+    let data = common::pattern(stride * 64);
+
+    bo.write(&data).unwrap();
+
+    let mapping = match bo.map(0, 0, 64, 64, transfer::Read) {
+        Ok(mapping) => mapping,
+        // Not every driver can map cursor buffers
+        Err(_) if !common::is_software(&device) => return,
+        Err(err) => panic!("{}", err),
+    };
+
+    for (row, expected) in mapping.chunks(mapping.stride() as usize).zip(data.chunks(stride)) {
+        assert_eq!(&row[..64 * 4], &expected[..64 * 4]);
+    }
+}
+
+#[test]
+fn write_pixels() {
+    let device = common::software();
+    let mut bo = BufferObject::new(&device, 64, 64, format::ARGB8888,
+                                   BufferUsage::CURSOR | BufferUsage::WRITE).unwrap();
+
+    bo.write_pixels(&[0x80ff_0000u32; 64 * 64]).unwrap();
+
+    let mapping = bo.map(0, 0, 64, 64, transfer::Read).unwrap();
+
+    assert_eq!(&mapping[..4], &[0x00, 0x00, 0xff, 0x80]);
+}
+
+#[test]
+fn write_planes_in_separate_fds() {
+    let device = common::software();
+    let usage = BufferUsage::RENDERING | BufferUsage::WRITE;
+    let luma = BufferObject::new(&device, 64, 32, format::NV12, usage).unwrap().export().unwrap();
+    let chroma = BufferObject::new(&device, 64, 32, format::NV12, usage).unwrap().export().unwrap();
+
+    let planes = [
+        ImportPlane { fd: luma.planes[0].fd.as_fd(), stride: luma.planes[0].stride, offset: 0 },
+        ImportPlane {
+            fd: chroma.planes[1].fd.as_fd(),
+            stride: chroma.planes[1].stride,
+            offset: chroma.planes[1].offset,
+        },
+    ];
+    let bo = BufferObject::import(&device, ImportSource::FdModifier {
+        width: 64,
+        height: 32,
+        format: format::NV12,
+        planes: &planes,
+        modifier: Modifier::LINEAR,
+    }, usage).unwrap();
+
+    let offset = planes[1].offset as usize;
+    let data = common::pattern(offset + planes[1].stride as usize * 16);
+
+    bo.write(&data).unwrap();
+
+    let read = |fd: BorrowedFd, offset: usize, len: usize| {
+        let mut buf = vec![0u8; len];
+        let n = unsafe {
+            libc::pread(fd.as_raw_fd(), buf.as_mut_ptr() as *mut _, len, offset as libc::off_t)
+        };
+
+        assert_eq!(n, len as isize);
+        buf
+    };
+
+    assert_eq!(read(planes[0].fd, 0, offset), &data[..offset]);
+    assert_eq!(read(planes[1].fd, offset, data.len() - offset), &data[offset..]);
+}
+
+#[test]
+fn write_too_large() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 64, format::ARGB8888,
+                               BufferUsage::CURSOR | BufferUsage::WRITE).unwrap();
+    let size = bo.stride() as usize * 64;
+
+    assert_eq!(bo.write(&vec![0; size + 1]),
+               Err(Error::InvalidWriteSize { len: size + 1, size }));
+    assert!(bo.write_pixels(&vec![0u32; size / 4 + 1]).is_err());
+}
+
+#[test]
+fn write_without_write_usage() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+
+    assert_eq!(bo.write(&[0; 64]).err().and_then(|err| err.op()), Some(Operation::Write));
+}
+
+#[test]
+fn map_read_write() {
+    let device = common::device();
+    let mut bo = BufferObject::new(&device, 32, 16, format::XRGB8888,
+                                   BufferUsage::RENDERING | BufferUsage::LINEAR).unwrap();
+
+    {
+        let mut mapping = bo.map(0, 0, 32, 16, transfer::Write).unwrap();
+        let stride = mapping.stride() as usize;
+
+        assert_eq!(mapping.len(), stride * 15 + 32 * 4);
+
+        let data = (0..mapping.len()).map(|i| (i % stride) as u8).collect::<Vec<_>>();
+        mapping.copy_from_slice(0, &data);
+    }
+
+    let mapping = bo.map(4, 2, 8, 8, transfer::Read).unwrap();
+
+    assert_eq!(mapping.len(), mapping.stride() as usize * 7 + 8 * 4);
+    assert_eq!(mapping[0], 16);
+}
+
+#[test]
+fn map_read_and_write() {
+    let device = common::software();
+    let mut bo = BufferObject::new(&device, 32, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let mut mapping = bo.map(0, 0, 32, 16, transfer::ReadWrite).unwrap();
+
+    mapping[0] = 1;
+    mapping[1] = mapping[0] + 1;
+
+    assert_eq!(&mapping[..2], &[1, 2]);
+}
+
+#[test]
+#[should_panic]
+fn map_write_past_end() {
+    let device = common::software();
+    let mut bo = BufferObject::new(&device, 32, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let mut mapping = bo.map(0, 0, 32, 16, transfer::Write).unwrap();
+    let len = mapping.len();
+
+    mapping.copy_from_slice(len - 1, &[0, 0]);
+}
+
+#[test]
+fn map_out_of_bounds() {
+    let device = common::software();
+    let mut bo = BufferObject::new(&device, 32, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let err = bo.map(16, 0, 32, 16, transfer::Read).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::Map));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn export() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 32, format::NV12, BufferUsage::RENDERING).unwrap();
+    let export = bo.export().unwrap();
+
+    assert_eq!(export.width, 64);
+    assert_eq!(export.height, 32);
+    assert_eq!(export.format, format::NV12);
+    assert_eq!(export.modifier, bo.modifier());
+    assert_eq!(export.planes.len(), bo.plane_count() as usize);
+
+    for (exported, plane) in export.planes.iter().zip(bo.planes()) {
+        assert!(exported.fd.as_raw_fd() >= 0);
+        assert_eq!(exported.offset, plane.offset());
+        assert_eq!(exported.stride, plane.stride());
+    }
+
+    let fd = bo.fd().unwrap();
+    assert_ne!(fd.as_raw_fd(), export.planes[0].fd.as_raw_fd());
+}
+
+#[test]
+fn import_fd() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 32, format::XRGB8888,
+                               BufferUsage::RENDERING | BufferUsage::LINEAR).unwrap();
+    let fd = bo.fd().unwrap();
+
+    let imported = BufferObject::import(&device, ImportSource::Fd {
+        fd: fd.as_fd(),
+        width: 64,
+        height: 32,
+        stride: bo.stride(),
+        format: format::XRGB8888,
+    }, BufferUsage::RENDERING).unwrap();
+
+    assert_eq!(imported.width(), 64);
+    assert_eq!(imported.height(), 32);
+    assert_eq!(imported.stride(), bo.stride());
+    assert_eq!(imported.format(), format::XRGB8888);
+}
+
+#[test]
+fn import_round_trip() {
+    let device = common::device();
+    let mut bo = BufferObject::new(&device, 64, 32, format::NV12,
+                                   BufferUsage::RENDERING | BufferUsage::LINEAR).unwrap();
+    let data = common::pattern(bo.stride() as usize * 32);
+
+    {
+        let mut mapping = bo.map(0, 0, 64, 32, transfer::Write).unwrap();
+        let len = mapping.len();
+
+        mapping.copy_from_slice(0, &data[..len]);
+    }
+
+    let export = bo.export().unwrap();
+    let planes = export.planes.iter()
+        .map(|plane| ImportPlane { fd: plane.fd.as_fd(), stride: plane.stride, offset: plane.offset })
+        .collect::<Vec<_>>();
+
+    let mut imported = BufferObject::import(&device, ImportSource::FdModifier {
+        width: export.width,
+        height: export.height,
+        format: export.format,
+        planes: &planes,
+        modifier: export.modifier,
+    }, BufferUsage::RENDERING).unwrap();
+
+    assert_eq!(imported.format(), format::NV12);
+    assert_eq!(imported.plane_count(), bo.plane_count());
+
+    for (imported, plane) in imported.planes().zip(bo.planes()) {
+        assert_eq!(imported.offset(), plane.offset());
+        assert_eq!(imported.stride(), plane.stride());
+    }
+
+    let mapping = imported.map(0, 0, 64, 32, transfer::Read).unwrap();
+    assert_eq!(&mapping[..], &data[..mapping.len()]);
+}
+
+#[test]
+fn import_invalid_plane_count() {
+    let device = common::device();
+    let bo = BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let fd = bo.fd().unwrap();
+    let plane = ImportPlane { fd: fd.as_fd(), stride: bo.stride(), offset: 0 };
+
+    for &count in &[0, 5] {
+        let planes = vec![plane; count];
+        let err = BufferObject::import(&device, ImportSource::FdModifier {
+            width: 64,
+            height: 64,
+            format: format::XRGB8888,
+            planes: &planes,
+            modifier: Modifier::LINEAR,
+        }, BufferUsage::RENDERING).err().unwrap();
+
+        assert_eq!(err, Error::InvalidPlaneCount(count));
+    }
+}
+
+#[test]
+fn import_larger_than_fd() {
+    let device = common::software();
+    let bo = BufferObject::new(&device, 16, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let fd = bo.fd().unwrap();
+
+    let err = BufferObject::import(&device, ImportSource::Fd {
+        fd: fd.as_fd(),
+        width: 16,
+        height: 4096,
+        stride: 1 << 20,
+        format: format::XRGB8888,
+    }, BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::ImportBufferObject));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn allocate_huge() {
+    let device = common::software();
+    let err = BufferObject::new(&device, 0x4000_0000, 1, format::XRGB8888,
+                                BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateBufferObject));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+
+    // Aligning the stride to 64 bytes overflows
+    let err = BufferObject::new(&device, u32::MAX, 1, format::R8,
+                                BufferUsage::RENDERING).err().unwrap();
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn import_huge() {
+    let device = common::software();
+    let bo = BufferObject::new(&device, 16, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let fd = bo.fd().unwrap();
+
+    let err = BufferObject::import(&device, ImportSource::Fd {
+        fd: fd.as_fd(),
+        width: 0x8000_0000,
+        height: 1,
+        stride: bo.stride(),
+        format: format::XRGB8888,
+    }, BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::ImportBufferObject));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn map_keeps_fd_offset() {
+    let device = common::software();
+    let mut bo = BufferObject::new(&device, 16, 16, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let fd = bo.fd().unwrap();
+
+    bo.map(0, 0, 16, 16, transfer::Read).unwrap();
+
+    assert_eq!(unsafe { libc::lseek(fd.as_raw_fd(), 0, libc::SEEK_CUR) }, 0);
+}
+
+#[test]
+fn import_wl_buffer_unsupported() {
+    let device = common::software();
+    // The software backend never dereferences the pointer
+    let source = unsafe { ImportSource::wl_buffer(std::ptr::null()) };
+    let err = BufferObject::import(&device, source, BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::ImportBufferObject));
+}
+
+struct DropCounter(Rc<Cell<u32>>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn user_data() {
+    let device = common::device();
+    let drops = Rc::new(Cell::new(0));
+
+    {
+        let mut bo = BufferObject::new(&device, 64, 64, format::XRGB8888,
+                                       BufferUsage::RENDERING).unwrap();
+
+        assert!(bo.user_data::<u32>().is_none());
+
+        bo.set_user_data(DropCounter(drops.clone()));
+        assert!(bo.user_data::<DropCounter>().is_some());
+        assert!(bo.user_data::<u32>().is_none());
+
+        bo.set_user_data(1u32);
+        assert_eq!(drops.get(), 1);
+
+        *bo.user_data_mut::<u32>().unwrap() += 1;
+        assert_eq!(bo.user_data::<u32>(), Some(&2));
+
+        bo.set_user_data(DropCounter(drops.clone()));
+    }
+
+    assert_eq!(drops.get(), 2);
+}
@@ -0,0 +1,72 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Helpers shared by the integration tests.
+
+// Not every test uses every helper
+#![allow(dead_code)]
+
+use gbm::Device;
+use std::fs;
+
+/// Open the first DRM node libgbm accepts, or a software Device without one
+///
+/// Render nodes are preferred over primary nodes. Set ```GBM_RS_TEST_SOFTWARE```
+/// to always use the software backend.
+pub fn device() -> Device {
+    if std::env::var_os("GBM_RS_TEST_SOFTWARE").is_none() {
+        let mut nodes = fs::read_dir("/dev/dri")
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("renderD") || name.starts_with("card"))
+            })
+            .collect::<Vec<_>>();
+
+        nodes.sort_by_key(|path| !path.to_string_lossy().contains("renderD"));
+
+        for node in nodes {
+            if let Ok(device) = Device::open(&node) {
+                return device;
+            }
+        }
+    }
+
+    software()
+}
+
+/// A software Device, for tests relying on its exact behaviour
+pub fn software() -> Device {
+    Device::software().expect("failed to create a software device")
+}
+
+/// Whether a Device uses the software backend
+pub fn is_software(device: &Device) -> bool {
+    device.backend_name() == "software"
+}
+
+/// Fill a buffer of ```len``` bytes with a recognizable pattern
+pub fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+}
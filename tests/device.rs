@@ -0,0 +1,147 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+extern crate gbm_rs as gbm;
+extern crate libc;
+
+mod common;
+
+use gbm::{format, BufferUsage, Error, Format, Modifier, Operation};
+use std::os::unix::prelude::*;
+
+#[test]
+fn open_missing_node() {
+    match gbm::Device::open("/dev/dri/does-not-exist") {
+        Err(err) => {
+            assert_eq!(err.op(), Some(Operation::OpenDevice));
+            assert_eq!(err.errno(), Some(libc::ENOENT));
+        }
+        Ok(_) => panic!("opened a missing node"),
+    }
+}
+
+#[test]
+fn load() {
+    // libgbm is loaded at runtime, and may not be installed at all
+    match gbm::load() {
+        Ok(_) | Err(gbm::Error::LibraryUnavailable(_)) => (),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[test]
+fn fd() {
+    let device = common::device();
+    let fd = device.fd().as_raw_fd();
+
+    assert!(fd >= 0);
+    assert!(unsafe { libc::fcntl(fd, libc::F_GETFD) } >= 0);
+}
+
+#[test]
+fn backend_name() {
+    assert!(!common::device().backend_name().is_empty());
+    assert_eq!(common::software().backend_name(), "software");
+}
+
+#[test]
+fn software_capabilities() {
+    let caps = common::software().capabilities();
+
+    assert!(caps.create_with_modifiers);
+    assert!(caps.create_with_modifiers2);
+    assert!(caps.bo_modifier);
+    assert!(caps.planes);
+    assert!(caps.fd_for_plane);
+    assert!(caps.format_modifier_plane_count);
+    assert!(caps.map);
+}
+
+#[test]
+fn format_supported() {
+    let device = common::device();
+
+    assert!(device.is_format_supported(format::XRGB8888, BufferUsage::RENDERING));
+    assert!(!device.is_format_supported(Format(0), BufferUsage::RENDERING));
+}
+
+#[test]
+fn software_format_support() {
+    let device = common::software();
+
+    for format in format::all() {
+        assert!(device.is_format_supported(format, BufferUsage::RENDERING), "{}", format);
+    }
+
+    assert!(!device.is_format_supported(format::XRGB8888, BufferUsage::PROTECTED));
+}
+
+#[test]
+fn format_modifier_plane_count() {
+    let device = common::software();
+
+    assert_eq!(device.format_modifier_plane_count(format::XRGB8888, Modifier::LINEAR), Some(1));
+    assert_eq!(device.format_modifier_plane_count(format::NV12, Modifier::LINEAR), Some(2));
+    assert_eq!(device.format_modifier_plane_count(format::YUV420, Modifier::LINEAR), Some(3));
+    assert_eq!(device.format_modifier_plane_count(format::NV12, gbm::modifier::I915_Y_TILED), None);
+    assert_eq!(device.format_modifier_plane_count(Format(0), Modifier::LINEAR), None);
+    assert_eq!(device.format_modifier_plane_count(format::XRGB8888, Modifier::INVALID), None);
+}
+
+#[test]
+fn format_table() {
+    let device = common::device();
+    let modifiers = gbm::modifier::all().collect::<Vec<_>>();
+    let table = device.format_table(format::all(), &modifiers,
+                                    BufferUsage::SCANOUT | BufferUsage::RENDERING);
+
+    let entry = table.iter()
+        .find(|entry| entry.format == format::XRGB8888)
+        .expect("XRGB8888 is missing from the table");
+    assert!(entry.usage.contains(BufferUsage::RENDERING));
+
+    for entry in &table {
+        for support in &entry.modifiers {
+            assert!(support.plane_count >= 1);
+        }
+    }
+
+    if common::is_software(&device) {
+        assert_eq!(table.len(), format::all().count());
+
+        for entry in &table {
+            let info = entry.format.info().unwrap();
+
+            assert_eq!(entry.modifiers.len(), 1);
+            assert_eq!(entry.modifiers[0].modifier, Modifier::LINEAR);
+            assert_eq!(entry.modifiers[0].plane_count, info.planes);
+        }
+    }
+}
+
+#[test]
+fn errors_convert_to_io() {
+    let err = Error::Failed { op: Operation::Map, errno: Some(libc::EINVAL) };
+    let io = std::io::Error::from(err);
+
+    assert_eq!(io.raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(err.to_string(), format!("failed to map buffer object: {}",
+                                        std::io::Error::from_raw_os_error(libc::EINVAL)));
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Only built with the egl feature, as it links libEGL
+#![cfg(feature = "egl")]
+
+extern crate gbm_rs as gbm;
+extern crate libc;
+
+mod common;
+
+use gbm::egl::{self, Display};
+use gbm::{format, BufferUsage, Operation, Surface};
+
+#[test]
+fn software_display() {
+    let device = common::software();
+    let err = Display::new(&device).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::GetDisplay));
+    assert_eq!(err.errno(), Some(libc::ENOSYS));
+}
+
+// Without a GPU there is nothing for EGL to render with, run these with
+// --ignored on a machine with one
+
+#[test]
+#[ignore = "needs a GPU with EGL"]
+fn swap_buffers() {
+    let device = common::device();
+
+    assert!(!common::is_software(&device), "no GPU to render with");
+
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888,
+                               BufferUsage::SCANOUT | BufferUsage::RENDERING).unwrap();
+    let display = Display::new(&device).unwrap();
+    let config = display.choose_config(format::XRGB8888, &[
+        egl::EGL_RENDERABLE_TYPE, egl::EGL_OPENGL_ES2_BIT,
+    ]).unwrap();
+    let context = display.create_context(config, &[egl::EGL_CONTEXT_CLIENT_VERSION, 2]).unwrap();
+    let window = display.create_window_surface(config, &surface).unwrap();
+
+    context.make_current(&window).unwrap();
+
+    let mut previous = None;
+
+    for _ in 0..8 {
+        assert!(surface.has_free_buffers());
+
+        let buffer = window.swap_buffers().unwrap();
+
+        assert_eq!(buffer.width(), 64);
+        assert_eq!(buffer.height(), 64);
+        assert_eq!(buffer.format(), format::XRGB8888);
+
+        // Keep the buffer locked for a frame, as if it was being scanned out
+        previous = Some(buffer);
+    }
+
+    drop(previous);
+}
+
+#[test]
+#[ignore = "needs a GPU with EGL"]
+fn no_matching_config() {
+    let device = common::device();
+
+    assert!(!common::is_software(&device), "no GPU to render with");
+
+    let display = Display::new(&device).unwrap();
+    let err = display.choose_config(gbm::Format(0), &[]).err().unwrap();
+
+    assert_eq!(err, gbm::Error::NoMatchingConfig(gbm::Format(0)));
+}
+
+#[test]
+#[ignore = "needs a GPU with EGL"]
+fn one_display_per_device() {
+    let device = common::device();
+
+    assert!(!common::is_software(&device), "no GPU to render with");
+
+    {
+        let _display = Display::new(&device).unwrap();
+
+        assert_eq!(Display::new(&device).err(), Some(gbm::Error::DisplayInUse));
+    }
+
+    Display::new(&device).unwrap();
+}
@@ -0,0 +1,196 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+extern crate gbm_rs as gbm;
+extern crate libc;
+
+use gbm::mock::Mock;
+use gbm::{format, transfer, BufferObject, BufferUsage, Operation, Surface};
+use std::os::fd::AsFd;
+
+#[test]
+fn fail_device() {
+    let mock = Mock::new();
+    mock.fail(Operation::CreateDevice, 1, libc::ENODEV);
+
+    let err = mock.device().err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateDevice));
+    assert_eq!(err.errno(), Some(libc::ENODEV));
+
+    mock.device().unwrap();
+    assert_eq!(mock.calls(Operation::CreateDevice), 2);
+}
+
+#[test]
+#[should_panic]
+fn fail_zeroth() {
+    Mock::new().fail(Operation::CreateBufferObject, 0, libc::ENOMEM);
+}
+
+#[test]
+fn fail_nth_allocation() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+
+    mock.fail(Operation::CreateBufferObject, 3, libc::ENOMEM);
+
+    let results = (0..4)
+        .map(|_| BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING))
+        .map(|result| result.err().and_then(|err| err.errno()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(results, [None, None, Some(libc::ENOMEM), None]);
+    assert_eq!(mock.calls(Operation::CreateBufferObject), 4);
+}
+
+#[test]
+fn fail_with_modifiers() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+
+    mock.fail(Operation::CreateBufferObject, 1, libc::ENOSPC);
+
+    let err = BufferObject::with_modifiers(&device, 64, 64, format::XRGB8888,
+                                           &[gbm::Modifier::LINEAR],
+                                           BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateBufferObject));
+    assert_eq!(err.errno(), Some(libc::ENOSPC));
+}
+
+#[test]
+fn fail_always() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+    let mut bo = BufferObject::new(&device, 64, 64, format::XRGB8888,
+                                   BufferUsage::RENDERING | BufferUsage::WRITE).unwrap();
+
+    mock.fail_always(Operation::Map, libc::EFAULT);
+    mock.fail_always(Operation::Write, libc::EIO);
+
+    for _ in 0..3 {
+        assert_eq!(bo.map(0, 0, 64, 64, transfer::Read).err().unwrap().errno(),
+                   Some(libc::EFAULT));
+        assert_eq!(bo.write(&[0; 16]).err().unwrap().errno(), Some(libc::EIO));
+    }
+
+    mock.succeed(Operation::Map);
+
+    bo.map(0, 0, 64, 64, transfer::Read).unwrap();
+    assert!(bo.write(&[0; 16]).is_err());
+}
+
+#[test]
+fn fail_export_and_import() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+    let bo = BufferObject::new(&device, 64, 64, format::NV12,
+                               BufferUsage::RENDERING | BufferUsage::LINEAR).unwrap();
+
+    mock.fail(Operation::ExportFd, 1, libc::EMFILE);
+
+    let err = bo.fd().err().unwrap();
+    assert_eq!(err.op(), Some(Operation::ExportFd));
+    assert_eq!(err.errno(), Some(libc::EMFILE));
+
+    // The second plane of the export fails
+    mock.fail(Operation::ExportFd, 2, libc::EMFILE);
+    assert_eq!(bo.export().err().unwrap().errno(), Some(libc::EMFILE));
+
+    let fd = bo.fd().unwrap();
+    mock.fail(Operation::ImportBufferObject, 1, libc::EINVAL);
+
+    let err = BufferObject::import(&device, gbm::ImportSource::Fd {
+        fd: fd.as_fd(),
+        width: 64,
+        height: 64,
+        stride: bo.stride(),
+        format: format::R8,
+    }, BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::ImportBufferObject));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn fail_surface() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+
+    mock.fail(Operation::CreateSurface, 1, libc::ENOMEM);
+
+    let err = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING)
+        .err().unwrap();
+    assert_eq!(err.op(), Some(Operation::CreateSurface));
+    assert_eq!(err.errno(), Some(libc::ENOMEM));
+
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    mock.fail(Operation::LockFrontBuffer, 1, libc::EAGAIN);
+
+    let err = surface.lock_front_buffer().err().unwrap();
+    assert_eq!(err.op(), Some(Operation::LockFrontBuffer));
+    assert_eq!(err.errno(), Some(libc::EAGAIN));
+
+    surface.lock_front_buffer().unwrap();
+}
+
+#[test]
+fn surface_buffers() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+
+    mock.set_surface_buffers(2);
+
+    let first = surface.lock_front_buffer().unwrap();
+    let _second = surface.lock_front_buffer().unwrap();
+
+    assert!(!surface.has_free_buffers());
+    assert_eq!(surface.lock_front_buffer().err().unwrap().errno(), Some(libc::EBUSY));
+
+    first.release();
+    assert!(surface.has_free_buffers());
+}
+
+#[test]
+fn no_free_buffers() {
+    let mock = Mock::new();
+    let device = mock.device().unwrap();
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+
+    mock.set_surface_buffers(0);
+
+    for _ in 0..3 {
+        assert!(!surface.has_free_buffers());
+        assert_eq!(surface.lock_front_buffer().err().unwrap().errno(), Some(libc::EBUSY));
+    }
+}
+
+#[test]
+fn shared_between_clones() {
+    let mock = Mock::new();
+    let device = mock.clone().device().unwrap();
+
+    mock.fail(Operation::CreateBufferObject, 1, libc::ENOMEM);
+
+    assert!(BufferObject::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).is_err());
+    assert_eq!(mock.clone().calls(Operation::CreateBufferObject), 1);
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+extern crate gbm_rs as gbm;
+
+use gbm::modifier::{self, Vendor};
+use gbm::Modifier;
+
+// Values from drm_fourcc.h, so a mistake in how modifiers are built shows up
+#[test]
+fn kernel_values() {
+    let modifiers = [
+        (Modifier::LINEAR, 0x0000000000000000),
+        (Modifier::INVALID, 0x00ffffffffffffff),
+        (modifier::I915_X_TILED, 0x0100000000000001),
+        (modifier::I915_Y_TILED, 0x0100000000000002),
+        (modifier::SAMSUNG_64_32_TILE, 0x0400000000000001),
+        (modifier::QCOM_COMPRESSED, 0x0500000000000001),
+        (modifier::BROADCOM_VC4_T_TILED, 0x0700000000000001),
+        (modifier::NVIDIA_16BX2_BLOCK_ONE_GOB, 0x0300000000000010),
+        (modifier::NVIDIA_16BX2_BLOCK_FOUR_GOB, 0x0300000000000012),
+        (modifier::NVIDIA_16BX2_BLOCK_THIRTYTWO_GOB, 0x0300000000000015),
+        (modifier::nvidia_block_linear_2d(0, 1, 0, 0xfe, 4), 0x03000000004fe014),
+    ];
+
+    for &(modifier, value) in &modifiers {
+        assert_eq!(modifier.0, value, "{}", modifier);
+    }
+}
+
+#[test]
+fn vendor() {
+    assert_eq!(modifier::I915_X_TILED.vendor(), Vendor::Intel);
+    assert_eq!(modifier::NVIDIA_16BX2_BLOCK_ONE_GOB.vendor(), Vendor::Nvidia);
+    assert_eq!(modifier::NVIDIA_16BX2_BLOCK_ONE_GOB.value(), 0x10);
+}
@@ -0,0 +1,143 @@
+// Copyright (c) 2015 Scott Anderson <ascent12@hotmail.com>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+extern crate gbm_rs as gbm;
+extern crate libc;
+
+mod common;
+
+use gbm::{format, transfer, BufferUsage, Format, Modifier, Operation, Surface};
+
+// Without a renderer libgbm has no front buffer to hand out, so locking
+// buffers is only tested with the software backend.
+
+#[test]
+fn create() {
+    let device = common::device();
+    let surface = Surface::new(&device, 640, 480, format::XRGB8888,
+                               BufferUsage::SCANOUT | BufferUsage::RENDERING).unwrap();
+
+    assert!(std::ptr::eq(surface.device(), &device));
+    assert!(!surface.c_struct().is_null());
+    assert!(surface.has_free_buffers());
+}
+
+#[test]
+fn create_invalid() {
+    let device = common::device();
+    let err = Surface::new(&device, 640, 480, Format(0), BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateSurface));
+}
+
+#[test]
+fn with_modifiers() {
+    let device = common::device();
+
+    Surface::with_modifiers(&device, 640, 480, format::XRGB8888, &[Modifier::LINEAR],
+                            BufferUsage::RENDERING).unwrap();
+}
+
+#[test]
+fn with_unsupported_modifiers() {
+    let device = common::software();
+    let err = Surface::with_modifiers(&device, 640, 480, format::XRGB8888,
+                                      &[gbm::modifier::I915_X_TILED],
+                                      BufferUsage::RENDERING).err().unwrap();
+
+    assert_eq!(err.op(), Some(Operation::CreateSurface));
+    assert_eq!(err.errno(), Some(libc::EINVAL));
+}
+
+#[test]
+fn lock_front_buffer() {
+    let device = common::software();
+    let surface = Surface::new(&device, 640, 480, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let buffer = surface.lock_front_buffer().unwrap();
+
+    assert!(std::ptr::eq(buffer.surface(), &surface));
+    assert_eq!(buffer.width(), 640);
+    assert_eq!(buffer.height(), 480);
+    assert_eq!(buffer.format(), format::XRGB8888);
+    assert!(buffer.stride() >= 640 * 4);
+}
+
+#[test]
+fn run_out_of_buffers() {
+    let device = common::software();
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let mut locked = Vec::new();
+
+    while surface.has_free_buffers() {
+        locked.push(surface.lock_front_buffer().unwrap());
+    }
+
+    assert!(!locked.is_empty());
+
+    let err = surface.lock_front_buffer().err().unwrap();
+    assert_eq!(err.op(), Some(Operation::LockFrontBuffer));
+
+    locked.pop().unwrap().release();
+
+    assert!(surface.has_free_buffers());
+    surface.lock_front_buffer().unwrap();
+}
+
+#[test]
+fn user_data_survives_release() {
+    let device = common::software();
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let mut locked = Vec::new();
+
+    while surface.has_free_buffers() {
+        let mut buffer = surface.lock_front_buffer().unwrap();
+        let id = locked.len();
+
+        assert!(buffer.user_data::<usize>().is_none());
+        buffer.set_user_data(id);
+        locked.push(buffer);
+    }
+
+    locked.clear();
+
+    // Buffers come back with the data attached when they were first locked
+    let mut seen = Vec::new();
+    while surface.has_free_buffers() {
+        let mut buffer = surface.lock_front_buffer().unwrap();
+
+        assert!(buffer.user_data_mut::<usize>().is_some());
+        seen.push(*buffer.user_data::<usize>().unwrap());
+        locked.push(buffer);
+    }
+
+    seen.sort();
+    assert_eq!(seen, (0..seen.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn map_locked_buffer() {
+    let device = common::software();
+    let surface = Surface::new(&device, 64, 64, format::XRGB8888, BufferUsage::RENDERING).unwrap();
+    let mut buffer = surface.lock_front_buffer().unwrap();
+
+    buffer.map(0, 0, 64, 64, transfer::ReadWrite).unwrap()[0] = 0xff;
+
+    assert_eq!(buffer.map(0, 0, 1, 1, transfer::Read).unwrap()[0], 0xff);
+}